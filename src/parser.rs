@@ -1,8 +1,39 @@
 use nom::IResult;
+use std::fmt;
 
-pub mod markdown;
+pub mod document;
 pub mod frontmatter;
+pub mod markdown;
 
 pub trait Parse<'a>: Sized {
-    fn parse(input: &'a str) -> IResult<&str, Self>;
+    fn parse(input: &'a str) -> IResult<&'a str, Self>;
+}
+
+/// A position in the source text, lines and columns start at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    /// Returns the location of `slice` within `source`, or `None` if it isn't part of `source`.
+    pub fn of(source: &str, slice: &str) -> Option<Self> {
+        let offset = (slice.as_ptr() as usize).checked_sub(source.as_ptr() as usize)?;
+        let before = source.get(..offset)?;
+
+        let line = before.matches('\n').count() + 1;
+        let column = before[before.rfind('\n').map_or(0, |i| i + 1)..]
+            .chars()
+            .count()
+            + 1;
+
+        Some(Self { line, column })
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
 }
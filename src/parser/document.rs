@@ -0,0 +1,149 @@
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{line_ending, space0},
+    combinator::{eof, opt, peek, recognize},
+    multi::many_till,
+    sequence::pair,
+    IResult,
+};
+
+use super::frontmatter::{self, Tokens};
+use super::markdown::{parse_line, Block, Options};
+use super::Parse;
+
+/// A markdown document, optionally starting with YAML frontmatter between `---` lines.
+///
+/// # Example
+/// ```
+/// use mdx_rs::parser::{document::Document, Parse};
+///
+/// let (_, document) = Document::parse("---\ntitle: Hello\n---\n# Hello\n").unwrap();
+///
+/// assert_eq!(document.frontmatter, Some("title: Hello\n"));
+/// assert_eq!(document.blocks.len(), 1);
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Document<'a> {
+    /// The YAML between the fences, frontmatter is only recognised at the very start of the input.
+    pub frontmatter: Option<&'a str>,
+    pub blocks: Vec<Block<'a>>,
+}
+
+impl<'a> Parse<'a> for Document<'a> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        Self::parse_with(input, &Options::default())
+    }
+}
+
+impl<'a> Document<'a> {
+    pub fn parse_with(input: &'a str, options: &Options) -> IResult<&'a str, Self> {
+        let (rest, frontmatter) = opt(parse_frontmatter)(input)?;
+        let (rest, blocks) = Block::parse_with(rest, options)?;
+
+        Ok((
+            rest,
+            Self {
+                frontmatter,
+                blocks,
+            },
+        ))
+    }
+
+    /// Parses the frontmatter into a YAML document.
+    pub fn metadata(&self) -> Result<Option<frontmatter::Document<'a>>, frontmatter::Error> {
+        let Some(yaml) = self.frontmatter else {
+            return Ok(None);
+        };
+
+        let (_, tokens) = Tokens::parse(yaml).map_err(|_| frontmatter::Error::Tokenize)?;
+        frontmatter::Document::from_tokens(&tokens).map(Some)
+    }
+}
+
+/// Parses the frontmatter, which is opened by a `---` line and closed by a `---` or `...` line.
+/// Without a closing line the opening `---` is a thematic break.
+fn parse_frontmatter(input: &str) -> IResult<&str, &str> {
+    fn fence<'a>(close: bool) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+        move |input| {
+            let end = alt((line_ending, eof));
+            let marker = match close {
+                true => alt((tag("---"), tag("...")))(input),
+                false => tag("---")(input),
+            };
+
+            let (rest, marker) = marker?;
+            let (rest, _) = pair(space0, end)(rest)?;
+            Ok((rest, marker))
+        }
+    }
+
+    let (rest, _) = fence(false)(input)?;
+    let (rest, yaml) = recognize(many_till(parse_line, peek(fence(true))))(rest)?;
+    let (rest, _) = fence(true)(rest)?;
+
+    Ok((rest, yaml))
+}
+
+#[cfg(test)]
+mod test_document {
+    use super::*;
+    use crate::parser::markdown::{Heading, HeadingId, ThematicBreak};
+    use indoc::indoc;
+
+    #[test]
+    fn test_frontmatter() {
+        let input = indoc! {"
+            ---
+            title: Hello
+            tags: [a, b]
+            ---
+
+            # Hello
+        "};
+
+        let (rest, document) = Document::parse(input).unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(document.frontmatter, Some("title: Hello\ntags: [a, b]\n"));
+
+        let metadata = document.metadata().unwrap().unwrap();
+        assert_eq!(metadata.get("title").unwrap().as_str().unwrap(), "Hello");
+
+        let (_, document) = Document::parse("---\n...\ntext\n").unwrap();
+        assert_eq!(document.frontmatter, Some(""));
+        assert_eq!(
+            document.metadata().unwrap(),
+            Some(frontmatter::Document(frontmatter::Value::Null))
+        );
+    }
+
+    #[test]
+    fn test_body_thematic_breaks() {
+        let input = indoc! {"
+            # Title
+
+            ---
+
+            text
+            ---
+        "};
+
+        let (_, document) = Document::parse(input).unwrap();
+        assert_eq!(document.frontmatter, None);
+        assert_eq!(document.metadata().unwrap(), None);
+        assert_eq!(document.blocks[2], Block::ThematicBreak(ThematicBreak));
+        assert_eq!(
+            document.blocks[4],
+            Block::Heading(Heading {
+                level: 2,
+                text: "text",
+                id: HeadingId::Slug("text".to_string()),
+            })
+        );
+
+        // an unclosed fence at the start is a thematic break
+        let (_, document) = Document::parse("---\n\nnot: frontmatter\n").unwrap();
+        assert_eq!(document.frontmatter, None);
+        assert_eq!(document.blocks[0], Block::ThematicBreak(ThematicBreak));
+    }
+}
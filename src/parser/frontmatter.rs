@@ -1,28 +1,58 @@
 use super::Parse;
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take, take_till1, take_until1},
-    character::complete::not_line_ending,
-    combinator::{eof, verify},
-    multi::many_till,
+    bytes::complete::{tag, take_till1},
+    character::complete::{char, line_ending, multispace1, not_line_ending, space0},
+    combinator::{eof, map, opt, value},
+    error::{make_error, ErrorKind},
+    multi::{many0_count, separated_list0},
+    sequence::{delimited, pair, preceded, terminated},
     IResult,
 };
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt,
+    ops::Range,
+    str::FromStr,
+};
 
-#[derive(Debug, PartialEq)]
+pub mod editor;
+pub mod schema;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Key<'a>(pub &'a str);
 
 impl<'a> Parse<'a> for Key<'a> {
     fn parse(input: &'a str) -> IResult<&'a str, Self> {
-        let (rest, key) = verify(take_until1(":"), |s: &str| !s.contains('\n'))(input)?;
-        let rest = rest.trim_start();
+        let (_, line) = not_line_ending(input)?;
+
+        // the key ends at the first `:` followed by whitespace or the end of the line, so values
+        // such as urls and times aren't split into keys
+        let end = line
+            .match_indices(':')
+            .map(|(i, _)| i)
+            .find(|&i| {
+                line[i + 1..]
+                    .chars()
+                    .next()
+                    .is_none_or(|c| c == ' ' || c == '\t')
+            })
+            .ok_or_else(|| nom::Err::Error(make_error(input, ErrorKind::TakeUntil)))?;
+
+        let key = line[..end].trim();
+
+        if key.is_empty() || key.starts_with(['"', '\'']) {
+            return Err(nom::Err::Error(make_error(input, ErrorKind::Verify)));
+        }
 
-        let (rest, _) = take(2usize)(rest)?;
+        let (rest, _) = space0(&input[end + 1..])?;
 
         Ok((rest, Self(key)))
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Indent;
 
 impl<'a> Parse<'a> for Indent {
@@ -32,52 +62,603 @@ impl<'a> Parse<'a> for Indent {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct ListItem;
 
 impl<'a> Parse<'a> for ListItem {
-    fn parse(input: &'a str) -> IResult<&str, Self> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
         let (rest, _) = tag("- ")(input)?;
 
         Ok((rest, Self))
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct LineBreak;
 
 impl<'a> Parse<'a> for LineBreak {
     fn parse(input: &'a str) -> IResult<&'a str, Self> {
-        let (rest, _) = tag("\n")(input)?;
+        let (rest, _) = line_ending(input)?;
         Ok((rest, Self))
     }
 }
 
-#[derive(Debug, PartialEq)]
+/// A plain (unquoted) scalar, running to the end of the line or the start of a comment.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Text<'a>(pub &'a str);
 
 impl<'a> Parse<'a> for Text<'a> {
     fn parse(input: &'a str) -> IResult<&'a str, Self> {
-        let (rest, contents) = not_line_ending(input)?;
-        Ok((rest, Self(contents)))
+        let (rest, line) = not_line_ending(input)?;
+
+        let (rest, contents) = match comment_start(line) {
+            Some(i) => (&input[i..], &line[..i]),
+            None => (rest, line),
+        };
+
+        Ok((rest, Self(contents.trim_end())))
     }
 }
 
-#[derive(Debug, PartialEq)]
+/// A comment, running from a `#` to the end of the line. Comments are ignored when building
+/// values.
+///
+/// # Example
+/// ```yaml
+/// title: C# tips # draft
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Comment<'a>(pub &'a str);
+
+impl<'a> Parse<'a> for Comment<'a> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        let (rest, _) = tag("#")(input)?;
+        let (rest, text) = not_line_ending(rest)?;
+        Ok((rest, Self(text.trim())))
+    }
+}
+
+/// Returns the position of a comment in a line. A `#` only starts a comment at the start of the
+/// line or after whitespace, so `C#` is plain text.
+fn comment_start(line: &str) -> Option<usize> {
+    line.match_indices('#')
+        .map(|(i, _)| i)
+        .find(|&i| i == 0 || line[..i].ends_with([' ', '\t']))
+}
+
+/// Parses the name of an anchor or alias.
+fn anchor_name(input: &str) -> IResult<&str, &str> {
+    let (rest, name) = take_till1(|c: char| c.is_whitespace() || ",[]{}".contains(c))(input)?;
+    let (rest, _) = space0(rest)?;
+    Ok((rest, name))
+}
+
+/// An anchor, naming the node it precedes so it can be reused elsewhere with an [`Alias`].
+///
+/// # Example
+/// ```yaml
+/// defaults: &defaults
+///   layout: post
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Anchor<'a>(pub &'a str);
+
+impl<'a> Parse<'a> for Anchor<'a> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        let (rest, _) = tag("&")(input)?;
+        let (rest, name) = anchor_name(rest)?;
+        Ok((rest, Self(name)))
+    }
+}
+
+/// A reference to an anchored node.
+///
+/// # Example
+/// ```yaml
+/// post:
+///   <<: *defaults
+///   layout: *layout
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Alias<'a>(pub &'a str);
+
+impl<'a> Parse<'a> for Alias<'a> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        let (rest, _) = tag("*")(input)?;
+        let (rest, name) = anchor_name(rest)?;
+        Ok((rest, Self(name)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuoteStyle {
+    Single,
+    Double,
+}
+
+/// A single or double quoted scalar.
+///
+/// # Example
+/// ```yaml
+/// title: "Rust: the good parts"
+/// quote: 'it''s escaped by doubling'
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quoted<'a> {
+    pub style: QuoteStyle,
+    /// The source between the quotes, with escapes and line breaks left as is.
+    pub raw: &'a str,
+}
+
+impl<'a> Parse<'a> for Quoted<'a> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        let (rest, style) = alt((
+            value(QuoteStyle::Double, char('"')),
+            value(QuoteStyle::Single, char('\'')),
+        ))(input)?;
+
+        let mut i = 0;
+
+        while let Some(c) = rest[i..].chars().next() {
+            match (style, c) {
+                (QuoteStyle::Double, '\\') => match unescape(&rest[i + 1..]) {
+                    Some((_, len)) => i += 1 + len,
+                    None => {
+                        return Err(nom::Err::Failure(make_error(
+                            &rest[i..],
+                            ErrorKind::Escaped,
+                        )))
+                    }
+                },
+                (QuoteStyle::Single, '\'') if rest[i + 1..].starts_with('\'') => i += 2,
+                (QuoteStyle::Double, '"') | (QuoteStyle::Single, '\'') => {
+                    let (rest, raw) = (&rest[i + 1..], &rest[..i]);
+                    let (rest, _) = space0(rest)?;
+                    return Ok((rest, Self { style, raw }));
+                }
+                _ => i += c.len_utf8(),
+            }
+        }
+
+        // unterminated scalar
+        Err(nom::Err::Failure(make_error(input, ErrorKind::Eof)))
+    }
+}
+
+impl<'a> Quoted<'a> {
+    /// Returns the value of the scalar with escapes resolved and line breaks folded.
+    pub fn value(&self) -> Cow<'a, str> {
+        let special: &[char] = match self.style {
+            QuoteStyle::Double => &['\\', '\n', '\r'],
+            QuoteStyle::Single => &['\'', '\n', '\r'],
+        };
+
+        if !self.raw.contains(special) {
+            return Cow::Borrowed(self.raw);
+        }
+
+        let mut out = String::with_capacity(self.raw.len());
+        let mut rest = self.raw;
+        // whitespace before a line break is dropped, unless it was escaped
+        let mut keep = 0;
+
+        while let Some(c) = rest.chars().next() {
+            rest = &rest[c.len_utf8()..];
+
+            match (self.style, c) {
+                (QuoteStyle::Double, '\\') => {
+                    let (decoded, len) =
+                        unescape(rest).expect("escapes are validated when parsing");
+                    rest = &rest[len..];
+
+                    match decoded {
+                        Some(c) => out.push(c),
+                        // an escaped line break joins the lines without a space
+                        None => rest = rest.trim_start_matches([' ', '\t']),
+                    }
+
+                    keep = out.len();
+                }
+                (QuoteStyle::Single, '\'') => {
+                    rest = &rest[1..];
+                    out.push('\'');
+                }
+                (_, '\n') | (_, '\r') => {
+                    let trimmed = out[keep..].trim_end_matches([' ', '\t']).len();
+                    out.truncate(keep + trimmed);
+
+                    if c == '\r' {
+                        rest = rest.strip_prefix('\n').unwrap_or(rest);
+                    }
+
+                    // a single line break folds into a space, each empty line after it is kept
+                    let mut breaks = 0;
+
+                    loop {
+                        rest = rest.trim_start_matches([' ', '\t']);
+
+                        match rest
+                            .strip_prefix("\r\n")
+                            .or_else(|| rest.strip_prefix('\n'))
+                        {
+                            Some(next) => {
+                                rest = next;
+                                breaks += 1;
+                            }
+                            None => break,
+                        }
+                    }
+
+                    match breaks {
+                        0 => out.push(' '),
+                        n => out.extend(std::iter::repeat_n('\n', n)),
+                    }
+
+                    keep = out.len();
+                }
+                (_, c) => out.push(c),
+            }
+        }
+
+        Cow::Owned(out)
+    }
+}
+
+/// Decodes the escape sequence following a `\` in a double quoted scalar, returning the character
+/// (`None` for an escaped line break) and the number of bytes consumed.
+fn unescape(input: &str) -> Option<(Option<char>, usize)> {
+    let c = input.chars().next()?;
+
+    let decoded = match c {
+        '0' => '\0',
+        'a' => '\x07',
+        'b' => '\x08',
+        't' | '\t' => '\t',
+        'n' => '\n',
+        'v' => '\x0b',
+        'f' => '\x0c',
+        'r' => '\r',
+        'e' => '\x1b',
+        ' ' => ' ',
+        '"' => '"',
+        '/' => '/',
+        '\\' => '\\',
+        'N' => '\u{85}',
+        '_' => '\u{a0}',
+        'L' => '\u{2028}',
+        'P' => '\u{2029}',
+        '\n' => return Some((None, 1)),
+        '\r' => return Some((None, if input[1..].starts_with('\n') { 2 } else { 1 })),
+        'x' | 'u' | 'U' => {
+            let len = match c {
+                'x' => 2,
+                'u' => 4,
+                _ => 8,
+            };
+
+            let hex = input.get(1..=len)?;
+
+            if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return None;
+            }
+
+            let decoded = char::from_u32(u32::from_str_radix(hex, 16).ok()?)?;
+            return Some((Some(decoded), 1 + len));
+        }
+        _ => return None,
+    };
+
+    Some((Some(decoded), c.len_utf8()))
+}
+
+/// Whether a block scalar keeps its line breaks (`|`) or folds them into spaces (`>`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockStyle {
+    Literal,
+    Folded,
+}
+
+/// How the final line break and trailing empty lines of a block scalar are handled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Chomping {
+    /// `-`, removes the final line break and any trailing empty lines.
+    Strip,
+    /// The default, keeps the final line break but removes trailing empty lines.
+    Clip,
+    /// `+`, keeps the final line break and any trailing empty lines.
+    Keep,
+}
+
+/// A literal or folded block scalar.
+///
+/// # Example
+/// ```yaml
+/// description: |-
+///   spans
+///   multiple lines
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockScalar<'a> {
+    /// The indicators starting the scalar, e.g. `|-`.
+    pub header: &'a str,
+    pub style: BlockStyle,
+    pub chomping: Chomping,
+    /// The lines of the scalar with the content indentation removed.
+    pub lines: Vec<&'a str>,
+}
+
+impl<'a> BlockScalar<'a> {
+    /// Parses a block scalar starting on a line indented by `parent` spaces. The contents are the
+    /// following lines that are indented further than `parent`.
+    pub fn parse_indented(input: &'a str, parent: usize) -> IResult<&'a str, Self> {
+        let (mut rest, style) = alt((
+            value(BlockStyle::Literal, char('|')),
+            value(BlockStyle::Folded, char('>')),
+        ))(input)?;
+
+        // the chomping and indentation indicators can be given in either order
+        let mut chomping = Chomping::Clip;
+        let mut indent = None;
+
+        for _ in 0..2 {
+            match rest.chars().next() {
+                Some('-') if chomping == Chomping::Clip => chomping = Chomping::Strip,
+                Some('+') if chomping == Chomping::Clip => chomping = Chomping::Keep,
+                Some(c @ '1'..='9') if indent.is_none() => indent = c.to_digit(10),
+                _ => break,
+            }
+
+            rest = &rest[1..];
+        }
+
+        let header = &input[..input.len() - rest.len()];
+
+        let (rest, _) = space0(rest)?;
+        let (mut rest, _) = opt(Comment::parse)(rest)?;
+        let (body, _) = alt((line_ending, eof))(rest)?;
+
+        let mut content_indent = indent.map(|indent| parent + indent as usize);
+        let mut lines = Vec::new();
+        let mut remaining = body;
+
+        for line in body.split_inclusive('\n') {
+            let text = line.trim_end_matches(['\n', '\r']);
+
+            if text.trim().is_empty() {
+                lines.push(
+                    content_indent
+                        .and_then(|indent| text.get(indent..))
+                        .unwrap_or(""),
+                );
+            } else {
+                let spaces = text.len() - text.trim_start_matches(' ').len();
+                let indent = *content_indent.get_or_insert(spaces);
+
+                if indent <= parent || spaces < indent {
+                    break;
+                }
+
+                lines.push(&text[indent..]);
+            }
+
+            // leave the final line break for the next token
+            rest = &remaining[text.len()..];
+            remaining = &remaining[line.len()..];
+        }
+
+        Ok((
+            rest,
+            Self {
+                header,
+                style,
+                chomping,
+                lines,
+            },
+        ))
+    }
+
+    /// Returns the value of the scalar with folding and chomping applied.
+    pub fn value(&self) -> String {
+        let end = self
+            .lines
+            .iter()
+            .rposition(|line| !line.is_empty())
+            .map_or(0, |i| i + 1);
+        let (content, trailing) = self.lines.split_at(end);
+
+        let mut out = match self.style {
+            BlockStyle::Literal => content.join("\n"),
+            BlockStyle::Folded => fold(content),
+        };
+
+        if !content.is_empty() && self.chomping != Chomping::Strip {
+            out.push('\n');
+        }
+
+        if self.chomping == Chomping::Keep {
+            out.extend(trailing.iter().map(|_| '\n'));
+        }
+
+        out
+    }
+}
+
+/// Folds the lines of a `>` block scalar. Line breaks between two regular lines become a space,
+/// while empty lines and lines that are indented further keep their line breaks.
+fn fold(lines: &[&str]) -> String {
+    let mut out = String::new();
+    let mut breaks = 0;
+    let mut previous = None;
+
+    for line in lines {
+        if line.is_empty() {
+            breaks += 1;
+            continue;
+        }
+
+        let regular = !line.starts_with([' ', '\t']);
+
+        match previous {
+            Some(true) if regular && breaks == 0 => out.push(' '),
+            Some(true) if regular => out.extend(std::iter::repeat_n('\n', breaks)),
+            Some(_) => out.extend(std::iter::repeat_n('\n', breaks + 1)),
+            None => out.extend(std::iter::repeat_n('\n', breaks)),
+        }
+
+        out.push_str(line);
+        previous = Some(regular);
+        breaks = 0;
+    }
+
+    out
+}
+
+/// A flow collection, written inline with JSON-like brackets and braces.
+///
+/// # Example
+/// ```yaml
+/// tags: [rust, mdx]
+/// author: {name: X, url: "https://example.com"}
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Flow<'a> {
+    Sequence(Vec<Flow<'a>>),
+    Mapping(Vec<(Key<'a>, Flow<'a>)>),
+    Quoted(Quoted<'a>),
+    Alias(Alias<'a>),
+    Text(Text<'a>),
+    /// A key in a mapping without a value.
+    Null,
+}
+
+impl<'a> Parse<'a> for Flow<'a> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        let (rest, flow) = alt((Self::sequence, Self::mapping))(input)?;
+        let (rest, _) = space0(rest)?;
+        Ok((rest, flow))
+    }
+}
+
+impl<'a> Flow<'a> {
+    fn node(input: &'a str) -> IResult<&'a str, Self> {
+        alt((
+            Self::sequence,
+            Self::mapping,
+            map(Quoted::parse, Flow::Quoted),
+            map(Alias::parse, Flow::Alias),
+            map(Self::plain, Flow::Text),
+        ))(input)
+    }
+
+    fn sequence(input: &'a str) -> IResult<&'a str, Self> {
+        // a sequence entry may be a single `key: value` pair
+        let entry = alt((
+            map(Self::pair, |pair| Flow::Mapping(vec![pair])),
+            Self::node,
+        ));
+
+        let (rest, items) = delimited(
+            pair(char('['), gap),
+            terminated(separated_list0(ws(char(',')), entry), opt(ws(char(',')))),
+            pair(gap, char(']')),
+        )(input)?;
+
+        Ok((rest, Flow::Sequence(items)))
+    }
+
+    fn mapping(input: &'a str) -> IResult<&'a str, Self> {
+        let entry = alt((Self::pair, map(Self::key, |key| (key, Flow::Null))));
+
+        let (rest, entries) = delimited(
+            pair(char('{'), gap),
+            terminated(separated_list0(ws(char(',')), entry), opt(ws(char(',')))),
+            pair(gap, char('}')),
+        )(input)?;
+
+        Ok((rest, Flow::Mapping(entries)))
+    }
+
+    fn pair(input: &'a str) -> IResult<&'a str, (Key<'a>, Self)> {
+        let (rest, key) = Self::key(input)?;
+        let (rest, _) = ws(char(':'))(rest)?;
+        let (rest, value) = opt(Self::node)(rest)?;
+
+        Ok((rest, (key, value.unwrap_or(Flow::Null))))
+    }
+
+    /// Parses a plain or quoted key, quoted keys are kept as written between the quotes.
+    fn key(input: &'a str) -> IResult<&'a str, Key<'a>> {
+        alt((
+            map(Quoted::parse, |quoted| Key(quoted.raw)),
+            map(Self::plain, |text| Key(text.0)),
+        ))(input)
+    }
+
+    /// Parses a plain scalar, which inside a flow collection ends at an indicator or a `:`
+    /// followed by whitespace.
+    fn plain(input: &'a str) -> IResult<&'a str, Text<'a>> {
+        let end = input
+            .char_indices()
+            .find(|&(i, c)| match c {
+                ',' | '[' | ']' | '{' | '}' | '\n' | '\r' => true,
+                '#' => input[..i].ends_with([' ', '\t']),
+                ':' => input[i + 1..]
+                    .chars()
+                    .next()
+                    .is_none_or(|c| c.is_whitespace() || ",[]{}".contains(c)),
+                _ => false,
+            })
+            .map_or(input.len(), |(i, _)| i);
+
+        let text = input[..end].trim_end();
+
+        if text.trim_start().is_empty() {
+            return Err(nom::Err::Error(make_error(input, ErrorKind::Verify)));
+        }
+
+        Ok((&input[end..], Text(text)))
+    }
+}
+
+/// Skips whitespace, line breaks and comments between the entries of a flow collection.
+fn gap(input: &str) -> IResult<&str, ()> {
+    let (rest, _) = many0_count(alt((multispace1, preceded(char('#'), not_line_ending))))(input)?;
+    Ok((rest, ()))
+}
+
+/// Wraps a parser so that it skips whitespace, line breaks and comments on either side.
+fn ws<'a, O>(
+    parser: impl FnMut(&'a str) -> IResult<&'a str, O>,
+) -> impl FnMut(&'a str) -> IResult<&'a str, O> {
+    delimited(gap, parser, gap)
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Token<'a> {
     Key(Key<'a>),
     ListItem(ListItem),
     Indent(Indent),
     LineBreak(LineBreak),
+    Quoted(Quoted<'a>),
+    BlockScalar(BlockScalar<'a>),
+    Flow(Flow<'a>),
+    Comment(Comment<'a>),
+    Anchor(Anchor<'a>),
+    Alias(Alias<'a>),
     Text(Text<'a>),
 }
 
 impl<'a> Parse<'a> for Token<'a> {
-    fn parse(input: &'a str) -> IResult<&str, Self> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
         alt((
             LineBreak::parse_token,
             Indent::parse_token,
             ListItem::parse_token,
+            Comment::parse_token,
+            Anchor::parse_token,
+            Alias::parse_token,
+            Quoted::parse_token,
+            Flow::parse_token,
             Key::parse_token,
             Text::parse_token,
         ))(input)
@@ -88,12 +669,46 @@ impl<'a> Parse<'a> for Token<'a> {
 pub struct Tokens<'a>(pub Vec<Token<'a>>);
 
 impl<'a> Parse<'a> for Tokens<'a> {
-    fn parse(input: &'a str) -> IResult<&str, Self> {
-        let (rest, (tokens, _)) = many_till(Token::parse, eof)(input)?;
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        let (rest, tokens) = Self::parse_spanned(input)?;
+        let tokens = tokens.into_iter().map(|(_, token)| token).collect();
         Ok((rest, Tokens(tokens)))
     }
 }
 
+impl<'a> Tokens<'a> {
+    /// Parses the input into tokens along with the byte range each token covers.
+    pub fn parse_spanned(input: &'a str) -> IResult<&'a str, Vec<(Range<usize>, Token<'a>)>> {
+        let mut tokens = Vec::new();
+        let mut rest = input;
+
+        while !rest.is_empty() {
+            // block scalars need the indentation of the line they start on to find their end
+            let consumed = &input[..input.len() - rest.len()];
+            let line = &consumed[consumed.rfind('\n').map_or(0, |i| i + 1)..];
+            let indent = line.len() - line.trim_start_matches(' ').len();
+
+            let (next, token) = alt((
+                |i| {
+                    let (rest, block) = BlockScalar::parse_indented(i, indent)?;
+                    Ok((rest, Token::BlockScalar(block)))
+                },
+                Token::parse,
+            ))(rest)?;
+
+            if next.len() == rest.len() {
+                return Err(nom::Err::Error(make_error(rest, ErrorKind::Many0)));
+            }
+
+            let start = input.len() - rest.len();
+            tokens.push((start..input.len() - next.len(), token));
+            rest = next;
+        }
+
+        Ok((rest, tokens))
+    }
+}
+
 impl<'a> From<Key<'a>> for Token<'a> {
     fn from(key: Key<'a>) -> Self {
         Token::Key(key)
@@ -118,6 +733,42 @@ impl<'a> From<LineBreak> for Token<'a> {
     }
 }
 
+impl<'a> From<Quoted<'a>> for Token<'a> {
+    fn from(quoted: Quoted<'a>) -> Self {
+        Token::Quoted(quoted)
+    }
+}
+
+impl<'a> From<BlockScalar<'a>> for Token<'a> {
+    fn from(block: BlockScalar<'a>) -> Self {
+        Token::BlockScalar(block)
+    }
+}
+
+impl<'a> From<Flow<'a>> for Token<'a> {
+    fn from(flow: Flow<'a>) -> Self {
+        Token::Flow(flow)
+    }
+}
+
+impl<'a> From<Comment<'a>> for Token<'a> {
+    fn from(comment: Comment<'a>) -> Self {
+        Token::Comment(comment)
+    }
+}
+
+impl<'a> From<Anchor<'a>> for Token<'a> {
+    fn from(anchor: Anchor<'a>) -> Self {
+        Token::Anchor(anchor)
+    }
+}
+
+impl<'a> From<Alias<'a>> for Token<'a> {
+    fn from(alias: Alias<'a>) -> Self {
+        Token::Alias(alias)
+    }
+}
+
 impl<'a> From<Text<'a>> for Token<'a> {
     fn from(text: Text<'a>) -> Self {
         Token::Text(text)
@@ -139,27 +790,450 @@ where
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Map<'a>(pub Vec<(Key<'a>, Value<'a>)>);
 
-#[derive(Debug)]
+impl<'a> Map<'a> {
+    pub fn get(&self, key: &str) -> Option<&Value<'a>> {
+        self.0
+            .iter()
+            .find(|(k, _)| k.0 == key)
+            .map(|(_, value)| value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct List<'a>(pub Vec<Value<'a>>);
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Value<'a> {
+    Null,
     Text(Text<'a>),
+    Quoted(Quoted<'a>),
+    BlockScalar(BlockScalar<'a>),
     List(List<'a>),
     Map(Map<'a>),
 }
 
-#[derive(Debug, thiserror::Error)]
-pub enum Error {}
+impl<'a> Value<'a> {
+    /// Returns the string value of a scalar, or `None` for lists, maps and nulls.
+    pub fn as_str(&self) -> Option<Cow<'a, str>> {
+        match self {
+            Value::Text(text) => Some(Cow::Borrowed(text.0)),
+            Value::Quoted(quoted) => Some(quoted.value()),
+            Value::BlockScalar(block) => Some(Cow::Owned(block.value())),
+            _ => None,
+        }
+    }
 
-pub struct Document<'a>(pub Vec<Value<'a>>);
+    pub fn get(&self, key: &str) -> Option<&Value<'a>> {
+        match self {
+            Value::Map(map) => map.get(key),
+            _ => None,
+        }
+    }
+
+    /// Whether the value is empty or a plain `null` or `~`.
+    pub fn is_null(&self) -> bool {
+        match self {
+            Value::Null => true,
+            Value::Text(Text(text)) => matches!(*text, "" | "~" | "null" | "Null" | "NULL"),
+            _ => false,
+        }
+    }
+
+    /// Returns the value of a plain `true` or `false`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Text(Text("true" | "True" | "TRUE")) => Some(true),
+            Value::Text(Text("false" | "False" | "FALSE")) => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a plain integer or float, including hex and octal integers and the
+    /// special `.inf` and `.nan` values.
+    pub fn as_f64(&self) -> Option<f64> {
+        let text = match self {
+            Value::Text(Text(text)) => *text,
+            _ => return None,
+        };
+
+        if let Some(hex) = text.strip_prefix("0x") {
+            return i64::from_str_radix(hex, 16).ok().map(|n| n as f64);
+        }
+
+        if let Some(octal) = text.strip_prefix("0o") {
+            return i64::from_str_radix(octal, 8).ok().map(|n| n as f64);
+        }
+
+        if matches!(text, ".nan" | ".NaN" | ".NAN") {
+            return Some(f64::NAN);
+        }
+
+        match text.trim_start_matches(['-', '+']) {
+            ".inf" | ".Inf" | ".INF" if text.starts_with('-') => Some(f64::NEG_INFINITY),
+            ".inf" | ".Inf" | ".INF" => Some(f64::INFINITY),
+            // rust also parses words such as `inf` and `infinity`, which are strings in yaml
+            digits if digits.starts_with(|c: char| c.is_ascii_digit() || c == '.') => {
+                text.parse().ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the value at a path relative to this value.
+    pub fn at(&self, path: &Path) -> Option<&Value<'a>> {
+        path.0
+            .iter()
+            .try_fold(self, |value, segment| match (segment, value) {
+                (Segment::Key(key), value) => value.get(key),
+                (Segment::Index(index), Value::List(list)) => list.0.get(*index),
+                _ => None,
+            })
+    }
+
+    /// Returns the source text at the start of the value, used to locate it in the input.
+    pub fn span(&self) -> Option<&'a str> {
+        match self {
+            Value::Null => None,
+            Value::Text(text) => Some(text.0),
+            Value::Quoted(quoted) => Some(quoted.raw),
+            Value::BlockScalar(block) => Some(block.header),
+            Value::List(list) => list.0.iter().find_map(Value::span),
+            Value::Map(map) => map.0.first().map(|(key, _)| key.0),
+        }
+    }
+}
+
+/// A segment of a [`Path`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// The path to a value within a document, such as `author[0].name`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Path(pub Vec<Segment>);
+
+impl FromStr for Path {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidPath(s.to_string());
+        let mut segments = Vec::new();
+
+        for (i, part) in s.split('.').enumerate() {
+            let (key, mut indices) = part.split_at(part.find('[').unwrap_or(part.len()));
+
+            match key {
+                // only the first part may start with an index, for documents that are lists
+                "" if i > 0 || indices.is_empty() => return Err(invalid()),
+                "" => {}
+                key => segments.push(Segment::Key(key.to_string())),
+            }
+
+            while !indices.is_empty() {
+                let (index, rest) = indices
+                    .strip_prefix('[')
+                    .and_then(|indices| indices.split_once(']'))
+                    .ok_or_else(invalid)?;
+
+                segments.push(Segment::Index(index.parse().map_err(|_| invalid())?));
+                indices = rest;
+            }
+        }
+
+        Ok(Self(segments))
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                Segment::Key(key) if i == 0 => write!(f, "{}", key)?,
+                Segment::Key(key) => write!(f, ".{}", key)?,
+                Segment::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum Error {
+    #[error("unexpected token")]
+    UnexpectedToken,
+    #[error("invalid indentation")]
+    InvalidIndentation,
+    #[error("undefined alias `{0}`")]
+    UndefinedAlias(String),
+    #[error("merge key `<<` expects a map or a list of maps")]
+    InvalidMerge,
+    #[error("failed to tokenize frontmatter")]
+    Tokenize,
+    #[error("invalid path `{0}`")]
+    InvalidPath(String),
+    #[error("no value at `{0}`")]
+    NotFound(String),
+    #[error("`{0}` is not a map or list")]
+    NotACollection(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Document<'a>(pub Value<'a>);
 
 impl<'a> Document<'a> {
-    pub fn from_tokens(&self) -> Result<Self, Error> {
-        todo!()
+    pub fn from_tokens(tokens: &Tokens<'a>) -> Result<Self, Error> {
+        let mut builder = Builder {
+            lines: lines(&tokens.0),
+            pos: 0,
+            anchors: HashMap::new(),
+        };
+
+        let value = builder.node()?;
+
+        if builder.pos < builder.lines.len() {
+            return Err(Error::UnexpectedToken);
+        }
+
+        Ok(Self(value))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value<'a>> {
+        self.0.get(key)
+    }
+}
+
+/// A line of tokens, with the leading indentation removed.
+#[derive(Clone, Copy)]
+struct Line<'t, 'a> {
+    indent: usize,
+    tokens: &'t [Token<'a>],
+}
+
+/// Splits the tokens into non-empty lines.
+fn lines<'t, 'a>(tokens: &'t [Token<'a>]) -> Vec<Line<'t, 'a>> {
+    tokens
+        .split(|token| matches!(token, Token::LineBreak(_)))
+        .map(|line| {
+            // comments run to the end of the line and don't contribute to values
+            let line = match line {
+                [line @ .., Token::Comment(_)] => line,
+                line => line,
+            };
+
+            let indent = line
+                .iter()
+                .take_while(|token| matches!(token, Token::Indent(_)))
+                .count();
+
+            Line {
+                indent,
+                tokens: &line[indent..],
+            }
+        })
+        .filter(|line| !line.tokens.is_empty())
+        .collect()
+}
+
+/// Builds values from the lines of a token stream, using their indentation to nest lists and
+/// maps.
+struct Builder<'t, 'a> {
+    lines: Vec<Line<'t, 'a>>,
+    pos: usize,
+    anchors: HashMap<&'a str, Value<'a>>,
+}
+
+impl<'t, 'a> Builder<'t, 'a> {
+    fn peek(&self) -> Option<Line<'t, 'a>> {
+        self.lines.get(self.pos).copied()
+    }
+
+    fn node(&mut self) -> Result<Value<'a>, Error> {
+        let line = match self.peek() {
+            Some(line) => line,
+            None => return Ok(Value::Null),
+        };
+
+        match line.tokens[0] {
+            Token::ListItem(_) => self.list(line.indent),
+            Token::Key(_) => self.map(line.indent),
+            _ => {
+                self.pos += 1;
+                self.value(line.indent, line.tokens, false)
+            }
+        }
+    }
+
+    /// Builds the value of a key or list item from the tokens following it on the same line, or
+    /// from the lines nested below it.
+    fn value(
+        &mut self,
+        indent: usize,
+        tokens: &[Token<'a>],
+        is_key: bool,
+    ) -> Result<Value<'a>, Error> {
+        let (anchor, tokens) = match tokens {
+            [Token::Anchor(anchor), tokens @ ..] => (Some(anchor.0), tokens),
+            tokens => (None, tokens),
+        };
+
+        let value = match tokens {
+            [] => match self.peek() {
+                // a list may share the indentation of the key it belongs to
+                Some(next)
+                    if is_key
+                        && next.indent == indent
+                        && matches!(next.tokens[0], Token::ListItem(_)) =>
+                {
+                    self.list(indent)?
+                }
+                Some(next) if next.indent > indent => self.node()?,
+                _ => Value::Null,
+            },
+            [Token::Text(text)] => Value::Text(*text),
+            [Token::Quoted(quoted)] => Value::Quoted(*quoted),
+            [Token::BlockScalar(block)] => Value::BlockScalar(block.clone()),
+            [Token::Flow(flow)] => self.flow(flow)?,
+            [Token::Alias(alias)] => self.alias(alias)?,
+            _ => return Err(Error::UnexpectedToken),
+        };
+
+        if let Some(anchor) = anchor {
+            self.anchors.insert(anchor, value.clone());
+        }
+
+        Ok(value)
+    }
+
+    fn map(&mut self, indent: usize) -> Result<Value<'a>, Error> {
+        let mut entries = Vec::new();
+        let mut merges = Vec::new();
+
+        while let Some(line) = self.peek() {
+            if line.indent < indent {
+                break;
+            }
+
+            if line.indent > indent {
+                return Err(Error::InvalidIndentation);
+            }
+
+            let key = match line.tokens[0] {
+                Token::Key(key) => key,
+                _ => return Err(Error::UnexpectedToken),
+            };
+
+            self.pos += 1;
+
+            let value = self.value(indent, &line.tokens[1..], true)?;
+
+            if key.0 == "<<" {
+                merges.push((entries.len(), value));
+            } else {
+                entries.push((key, value));
+            }
+        }
+
+        // explicit keys take precedence over merged ones, and earlier merged maps over later ones
+        let mut seen: HashSet<&str> = entries.iter().map(|(key, _)| key.0).collect();
+        let mut merged = Vec::new();
+
+        for (index, value) in merges {
+            let maps = match value {
+                Value::Map(map) => vec![map],
+                Value::List(List(items)) => items
+                    .into_iter()
+                    .map(|item| match item {
+                        Value::Map(map) => Ok(map),
+                        _ => Err(Error::InvalidMerge),
+                    })
+                    .collect::<Result<_, _>>()?,
+                _ => return Err(Error::InvalidMerge),
+            };
+
+            let inherited: Vec<_> = maps
+                .into_iter()
+                .flat_map(|map| map.0)
+                .filter(|(key, _)| seen.insert(key.0))
+                .collect();
+
+            merged.push((index, inherited));
+        }
+
+        for (index, inherited) in merged.into_iter().rev() {
+            entries.splice(index..index, inherited);
+        }
+
+        Ok(Value::Map(Map(entries)))
+    }
+
+    fn list(&mut self, indent: usize) -> Result<Value<'a>, Error> {
+        let mut items = Vec::new();
+
+        while let Some(line) = self.peek() {
+            if line.indent > indent {
+                return Err(Error::InvalidIndentation);
+            }
+
+            if line.indent < indent || !matches!(line.tokens[0], Token::ListItem(_)) {
+                break;
+            }
+
+            let tokens = &line.tokens[1..];
+
+            let item = match tokens.first() {
+                // a nested node starting on the same line as the item continues on the following
+                // lines, indented past the `- `
+                Some(Token::Key(_)) | Some(Token::ListItem(_)) => {
+                    self.lines[self.pos] = Line {
+                        indent: indent + 1,
+                        tokens,
+                    };
+                    self.node()?
+                }
+                _ => {
+                    self.pos += 1;
+                    self.value(indent, tokens, false)?
+                }
+            };
+
+            items.push(item);
+        }
+
+        Ok(Value::List(List(items)))
+    }
+
+    fn flow(&self, flow: &Flow<'a>) -> Result<Value<'a>, Error> {
+        let value = match flow {
+            Flow::Sequence(items) => Value::List(List(
+                items
+                    .iter()
+                    .map(|item| self.flow(item))
+                    .collect::<Result<_, _>>()?,
+            )),
+            Flow::Mapping(entries) => Value::Map(Map(entries
+                .iter()
+                .map(|(key, value)| Ok((*key, self.flow(value)?)))
+                .collect::<Result<_, _>>()?)),
+            Flow::Quoted(quoted) => Value::Quoted(*quoted),
+            Flow::Alias(alias) => self.alias(alias)?,
+            Flow::Text(text) => Value::Text(*text),
+            Flow::Null => Value::Null,
+        };
+
+        Ok(value)
+    }
+
+    fn alias(&self, alias: &Alias<'a>) -> Result<Value<'a>, Error> {
+        self.anchors
+            .get(alias.0)
+            .cloned()
+            .ok_or_else(|| Error::UndefinedAlias(alias.0.to_string()))
     }
 }
 
@@ -176,6 +1250,16 @@ mod test_frontmatter {
 
         assert_eq!(rest, "value");
         assert_eq!(key, Key("key"));
+
+        let (rest, key) = Key::parse("url: https://example.com").unwrap();
+
+        assert_eq!(rest, "https://example.com");
+        assert_eq!(key, Key("url"));
+
+        let (rest, key) = Key::parse("author:\n").unwrap();
+
+        assert_eq!(rest, "\n");
+        assert_eq!(key, Key("author"));
     }
 
     #[test]
@@ -187,6 +1271,236 @@ mod test_frontmatter {
         assert_eq!(rest, "list item\n");
     }
 
+    #[test]
+    fn test_parse_quoted() {
+        let (rest, quoted) = Quoted::parse("\"a: b\"\n").unwrap();
+
+        assert_eq!(rest, "\n");
+        assert_eq!(quoted.style, QuoteStyle::Double);
+        assert_eq!(quoted.value(), "a: b");
+
+        let (_, quoted) = Quoted::parse(r#""tab\tquote\" é\x41""#).unwrap();
+        assert_eq!(quoted.value(), "tab\tquote\" \u{e9}A");
+
+        let (_, quoted) = Quoted::parse("'it''s \\n'").unwrap();
+        assert_eq!(quoted.style, QuoteStyle::Single);
+        assert_eq!(quoted.value(), "it's \\n");
+
+        let (_, quoted) = Quoted::parse("\"folded  \n  over\n\n  lines\\\n  joined\"").unwrap();
+        assert_eq!(quoted.value(), "folded over\nlinesjoined");
+
+        assert!(Quoted::parse(r#""bad \q escape""#).is_err());
+        assert!(Quoted::parse("'unterminated").is_err());
+    }
+
+    #[test]
+    fn test_parse_block_scalar() {
+        let input = indoc! {"
+            |
+              first line
+                indented
+
+              last line
+
+            next: value
+        "};
+
+        let (rest, block) = BlockScalar::parse_indented(input, 0).unwrap();
+
+        assert_eq!(rest, "\nnext: value\n");
+        assert_eq!(block.style, BlockStyle::Literal);
+        assert_eq!(block.chomping, Chomping::Clip);
+        assert_eq!(block.value(), "first line\n  indented\n\nlast line\n");
+
+        let input = indoc! {"
+            >-
+              folded
+              text
+
+              paragraph
+                indented
+              end
+            next
+        "};
+
+        let (_, block) = BlockScalar::parse_indented(input, 0).unwrap();
+
+        assert_eq!(block.header, ">-");
+        assert_eq!(block.value(), "folded text\nparagraph\n  indented\nend");
+
+        let (_, block) = BlockScalar::parse_indented("|+2\n     code\n\n", 0).unwrap();
+
+        assert_eq!(block.chomping, Chomping::Keep);
+        assert_eq!(block.value(), "   code\n\n");
+    }
+
+    #[test]
+    fn test_parse_flow() {
+        let (rest, flow) = Flow::parse("[rust, mdx]\nnext: value").unwrap();
+
+        assert_eq!(rest, "\nnext: value");
+        assert_eq!(
+            flow,
+            Flow::Sequence(vec![Flow::Text(Text("rust")), Flow::Text(Text("mdx"))])
+        );
+
+        let (_, flow) = Flow::parse(r#"{name: X, url: "https://example.com", draft}"#).unwrap();
+
+        assert_eq!(
+            flow,
+            Flow::Mapping(vec![
+                (Key("name"), Flow::Text(Text("X"))),
+                (
+                    Key("url"),
+                    Flow::Quoted(Quoted {
+                        style: QuoteStyle::Double,
+                        raw: "https://example.com"
+                    })
+                ),
+                (Key("draft"), Flow::Null),
+            ])
+        );
+
+        let input = indoc! {"
+            [
+              [a, 'b, c'],
+              {x: [1, 2], \"y\": {}},
+              k: v,
+            ]
+        "};
+
+        let (_, flow) = Flow::parse(input).unwrap();
+
+        assert_eq!(
+            flow,
+            Flow::Sequence(vec![
+                Flow::Sequence(vec![
+                    Flow::Text(Text("a")),
+                    Flow::Quoted(Quoted {
+                        style: QuoteStyle::Single,
+                        raw: "b, c"
+                    }),
+                ]),
+                Flow::Mapping(vec![
+                    (
+                        Key("x"),
+                        Flow::Sequence(vec![Flow::Text(Text("1")), Flow::Text(Text("2"))])
+                    ),
+                    (Key("y"), Flow::Mapping(vec![])),
+                ]),
+                Flow::Mapping(vec![(Key("k"), Flow::Text(Text("v")))]),
+            ])
+        );
+
+        assert!(Flow::parse("[a, b").is_err());
+        assert_eq!(Flow::parse("[]").unwrap().1, Flow::Sequence(vec![]));
+    }
+
+    #[test]
+    fn test_parse_comments() {
+        let (rest, text) = Text::parse("C# tips # draft\n").unwrap();
+
+        assert_eq!(rest, "# draft\n");
+        assert_eq!(text, Text("C# tips"));
+
+        let input = indoc! {"
+            # leading comment
+            title: C# tips # draft
+            tags: [a, # first
+              b]
+        "};
+
+        let (_, tokens) = Tokens::parse(input).unwrap();
+
+        assert_eq!(tokens.0[0], Token::Comment(Comment("leading comment")));
+        assert_eq!(
+            &tokens.0[2..5],
+            &[
+                Token::Key(Key("title")),
+                Token::Text(Text("C# tips")),
+                Token::Comment(Comment("draft")),
+            ]
+        );
+
+        let document = Document::from_tokens(&tokens).unwrap();
+
+        assert_eq!(document.get("title"), Some(&Value::Text(Text("C# tips"))));
+        assert_eq!(
+            document.get("tags"),
+            Some(&Value::List(List(vec![
+                Value::Text(Text("a")),
+                Value::Text(Text("b")),
+            ])))
+        );
+    }
+
+    #[test]
+    fn test_anchors_and_aliases() {
+        let input = indoc! {"
+            defaults: &defaults
+              layout: post
+              draft: false
+            author: &author Jane
+            post:
+              <<: *defaults
+              draft: true
+            editors: [*author, Joe]
+        "};
+
+        let (_, tokens) = Tokens::parse(input).unwrap();
+
+        assert_eq!(tokens.0[1], Token::Anchor(Anchor("defaults")));
+
+        let document = Document::from_tokens(&tokens).unwrap();
+
+        assert_eq!(
+            document.get("post"),
+            Some(&Value::Map(Map(vec![
+                (Key("layout"), Value::Text(Text("post"))),
+                (Key("draft"), Value::Text(Text("true"))),
+            ])))
+        );
+        assert_eq!(
+            document.get("editors"),
+            Some(&Value::List(List(vec![
+                Value::Text(Text("Jane")),
+                Value::Text(Text("Joe")),
+            ])))
+        );
+
+        let input = indoc! {"
+            a: &a {x: 1, y: 1}
+            b: &b {y: 2, z: 2}
+            c:
+              <<: [*a, *b]
+              x: 3
+        "};
+
+        let (_, tokens) = Tokens::parse(input).unwrap();
+        let document = Document::from_tokens(&tokens).unwrap();
+
+        assert_eq!(
+            document.get("c"),
+            Some(&Value::Map(Map(vec![
+                (Key("y"), Value::Text(Text("1"))),
+                (Key("z"), Value::Text(Text("2"))),
+                (Key("x"), Value::Text(Text("3"))),
+            ])))
+        );
+
+        let (_, tokens) = Tokens::parse("a: *missing\n").unwrap();
+        assert_eq!(
+            Document::from_tokens(&tokens).unwrap_err(),
+            Error::UndefinedAlias("missing".to_string())
+        );
+
+        let (_, tokens) = Tokens::parse("a: &a text\nb:\n  <<: *a\n").unwrap();
+        assert_eq!(
+            Document::from_tokens(&tokens).unwrap_err(),
+            Error::InvalidMerge
+        );
+    }
+
     #[test]
     fn test_parse_tokens() {
         let input = indoc! {"
@@ -212,5 +1526,135 @@ mod test_frontmatter {
 
         let tokens = Tokens::parse(input).unwrap();
         dbg!(tokens);
+
+        let input = indoc! {r#"
+            title: "a: b"
+            description: |
+              a: b
+              - c
+            next: value
+        "#};
+
+        let (_, tokens) = Tokens::parse(input).unwrap();
+
+        assert_eq!(
+            tokens.0,
+            vec![
+                Token::Key(Key("title")),
+                Token::Quoted(Quoted {
+                    style: QuoteStyle::Double,
+                    raw: "a: b"
+                }),
+                Token::LineBreak(LineBreak),
+                Token::Key(Key("description")),
+                Token::BlockScalar(BlockScalar {
+                    header: "|",
+                    style: BlockStyle::Literal,
+                    chomping: Chomping::Clip,
+                    lines: vec!["a: b", "- c"],
+                }),
+                Token::LineBreak(LineBreak),
+                Token::Key(Key("next")),
+                Token::Text(Text("value")),
+                Token::LineBreak(LineBreak),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_document_from_tokens() {
+        let input = indoc! {r#"
+            title: "a: b"
+            draft:
+            keywords:
+            - one
+            - 'two'
+            author:
+              - name: Author one
+                bio: >
+                  writes
+                  things
+              - name: Author two
+            summary: |-
+              line one
+              line two
+        "#};
+
+        let (_, tokens) = Tokens::parse(input).unwrap();
+        let document = Document::from_tokens(&tokens).unwrap();
+
+        assert_eq!(document.get("title").unwrap().as_str().unwrap(), "a: b");
+        assert_eq!(document.get("draft"), Some(&Value::Null));
+
+        let keywords = match document.get("keywords") {
+            Some(Value::List(list)) => list
+                .0
+                .iter()
+                .map(|value| value.as_str().unwrap())
+                .collect::<Vec<_>>(),
+            other => panic!("expected a list, got {:?}", other),
+        };
+        assert_eq!(keywords, vec!["one", "two"]);
+
+        let authors = match document.get("author") {
+            Some(Value::List(list)) => &list.0,
+            other => panic!("expected a list, got {:?}", other),
+        };
+        assert_eq!(authors.len(), 2);
+        assert_eq!(
+            authors[0].get("name").unwrap().as_str().unwrap(),
+            "Author one"
+        );
+        assert_eq!(
+            authors[0].get("bio").unwrap().as_str().unwrap(),
+            "writes things\n"
+        );
+        assert_eq!(
+            authors[1].get("name").unwrap().as_str().unwrap(),
+            "Author two"
+        );
+
+        assert_eq!(
+            document.get("summary").unwrap().as_str().unwrap(),
+            "line one\nline two"
+        );
+
+        let input = indoc! {"
+            tags: [rust, mdx]
+            author: {name: X, url: Y}
+            series:
+              - [one, two]
+        "};
+
+        let (_, tokens) = Tokens::parse(input).unwrap();
+        let document = Document::from_tokens(&tokens).unwrap();
+
+        let tags = match document.get("tags") {
+            Some(Value::List(list)) => list
+                .0
+                .iter()
+                .map(|value| value.as_str().unwrap())
+                .collect::<Vec<_>>(),
+            other => panic!("expected a list, got {:?}", other),
+        };
+        assert_eq!(tags, vec!["rust", "mdx"]);
+
+        let author = document.get("author").unwrap();
+        assert_eq!(author.get("name").unwrap().as_str().unwrap(), "X");
+        assert_eq!(author.get("url").unwrap().as_str().unwrap(), "Y");
+
+        assert_eq!(
+            document.get("series"),
+            Some(&Value::List(List(vec![Value::List(List(vec![
+                Value::Text(Text("one")),
+                Value::Text(Text("two")),
+            ]))])))
+        );
+
+        let (_, tokens) = Tokens::parse("key: value\n    nested: value\n").unwrap();
+        assert_eq!(
+            Document::from_tokens(&tokens).unwrap_err(),
+            Error::InvalidIndentation
+        );
     }
 }
@@ -0,0 +1,732 @@
+use super::{Document, Error, Path, Segment, Text, Token, Tokens, Value};
+use crate::parser::Parse;
+use std::{fmt, fmt::Write, ops::Range};
+
+/// A replacement of a byte range of the text.
+type Edit = (Range<usize>, String);
+
+/// Edits frontmatter in place. Only the lines of the values that change are rewritten, so
+/// comments, indentation and the order of keys are kept.
+///
+/// # Example
+/// ```
+/// use mdx_rs::parser::frontmatter::editor::Editor;
+///
+/// let mut editor = Editor::new("title: Post # the title\nlastmod: 2023-01-01\n").unwrap();
+/// editor.set("lastmod", "2024-06-01").unwrap();
+///
+/// assert_eq!(editor.as_str(), "title: Post # the title\nlastmod: 2024-06-01\n");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Editor {
+    text: String,
+}
+
+impl Editor {
+    pub fn new(source: &str) -> Result<Self, Error> {
+        let editor = Self {
+            text: source.to_string(),
+        };
+
+        editor.document()?;
+
+        Ok(editor)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn into_string(self) -> String {
+        self.text
+    }
+
+    pub fn document(&self) -> Result<Document<'_>, Error> {
+        let (_, tokens) = Tokens::parse(&self.text).map_err(|_| Error::Tokenize)?;
+        Document::from_tokens(&tokens)
+    }
+
+    /// Returns the value at a path such as `author.name` or `tags[0]`.
+    pub fn get(&self, path: &str) -> Result<Option<Value<'_>>, Error> {
+        let path: Path = path.parse()?;
+        Ok(self.document()?.0.at(&path).cloned())
+    }
+
+    /// Sets the value at a path to a string, quoting it when needed. A missing key is added at
+    /// the end of its map, along with any missing maps above it.
+    pub fn set(&mut self, path: &str, value: &str) -> Result<(), Error> {
+        self.set_raw(path, &scalar(value))
+    }
+
+    /// Sets the value at a path to the given yaml, such as `[a, b]` or `"quoted"`.
+    pub fn set_raw(&mut self, path: &str, source: &str) -> Result<(), Error> {
+        let path: Path = path.parse()?;
+        let mut layout = Layout::new(&self.text)?;
+
+        let edits = match layout.locate(&path)? {
+            Lookup::Found(node, _) => layout.replace(node, source),
+            Lookup::Missing {
+                depth,
+                region,
+                parent,
+            } => layout.insert(&path, depth, region, parent, source)?,
+        };
+
+        self.apply(edits)
+    }
+
+    /// Removes the value at a path along with its key, returning whether it existed.
+    pub fn remove(&mut self, path: &str) -> Result<bool, Error> {
+        let path: Path = path.parse()?;
+        let mut layout = Layout::new(&self.text)?;
+
+        let edits = match layout.locate(&path)? {
+            Lookup::Found(node, region) => layout.remove(node, region),
+            Lookup::Missing { .. } => return Ok(false),
+        };
+
+        self.apply(edits)?;
+
+        Ok(true)
+    }
+
+    /// Applies the edits, restoring the previous text if the result can't be parsed.
+    fn apply(&mut self, mut edits: Vec<Edit>) -> Result<(), Error> {
+        let original = self.text.clone();
+
+        // apply the edits from the end of the text so the earlier ranges stay valid
+        edits.sort_by_key(|(range, _)| std::cmp::Reverse((range.start, range.end)));
+
+        for (range, replacement) in edits {
+            self.text.replace_range(range, &replacement);
+        }
+
+        if let Err(error) = self.document() {
+            self.text = original;
+            return Err(error);
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Editor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Formats a string as a scalar, double quoting it when it can't be written as plain text.
+fn scalar(value: &str) -> String {
+    // a plain `true`, `123` or `null` would read back as a bool, number or null
+    let resolved = Value::Text(Text(value));
+    let typed = resolved.is_null() || resolved.as_bool().is_some() || resolved.as_f64().is_some();
+
+    let plain = !typed
+        && value.trim() == value
+        && !value.starts_with([
+            '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%', '@',
+            '`',
+        ])
+        && !value.starts_with("- ")
+        && value != "-"
+        && !value.contains(": ")
+        && !value.contains(" #")
+        && !value.ends_with(':')
+        && !value.contains(char::is_control);
+
+    if plain {
+        return value.to_string();
+    }
+
+    let mut out = String::from('"');
+
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+/// A line of the text, which may span several lines of source when it contains a block scalar
+/// or a multi-line flow collection.
+#[derive(Debug)]
+struct Line<'a> {
+    /// The byte range of the line, excluding the line break.
+    range: Range<usize>,
+    indent: usize,
+    /// The tokens following the indentation, excluding a trailing comment.
+    tokens: Vec<(Range<usize>, Token<'a>)>,
+    comment: Option<Range<usize>>,
+    /// Whether the `- ` starting the line was dropped to treat the line as the first key of the
+    /// map in a list item.
+    item: bool,
+}
+
+impl<'a> Line<'a> {
+    fn new(range: Range<usize>, mut tokens: Vec<(Range<usize>, Token<'a>)>) -> Self {
+        let indent = tokens
+            .iter()
+            .take_while(|(_, token)| matches!(token, Token::Indent(_)))
+            .count();
+
+        tokens.drain(..indent);
+
+        let comment = match tokens.last() {
+            Some((range, Token::Comment(_))) => Some(range.clone()),
+            _ => None,
+        };
+
+        if comment.is_some() {
+            tokens.pop();
+        }
+
+        Self {
+            range,
+            indent,
+            tokens,
+            comment,
+            item: false,
+        }
+    }
+
+    fn is_blank(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    fn first(&self) -> Option<&Token<'a>> {
+        self.tokens.first().map(|(_, token)| token)
+    }
+
+    /// Returns the index of the first token of the value following the line's key or `- `.
+    fn value_start(&self) -> usize {
+        match self.tokens.get(1) {
+            Some((_, Token::Anchor(_))) => 2,
+            _ => 1.min(self.tokens.len()),
+        }
+    }
+
+    fn value(&self) -> &[(Range<usize>, Token<'a>)] {
+        &self.tokens[self.value_start()..]
+    }
+}
+
+/// A map entry or list item, spanning the line it starts on and the lines nested below it.
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    line: usize,
+    /// The index after the last line of the node.
+    end: usize,
+    indent: usize,
+}
+
+/// The lines of a map or list, and the indentation of its keys or items.
+#[derive(Debug, Clone)]
+struct Region {
+    lines: Range<usize>,
+    indent: usize,
+}
+
+enum Lookup {
+    Found(Node, Region),
+    /// The path exists up to `depth`, `region` is where the missing segment would be.
+    Missing {
+        depth: usize,
+        region: Region,
+        parent: Option<Node>,
+    },
+}
+
+/// The lines of the text, used to find the source of the values at a path.
+struct Layout<'a> {
+    text: &'a str,
+    lines: Vec<Line<'a>>,
+}
+
+impl<'a> Layout<'a> {
+    fn new(text: &'a str) -> Result<Self, Error> {
+        let (_, tokens) = Tokens::parse_spanned(text).map_err(|_| Error::Tokenize)?;
+
+        let mut lines = Vec::new();
+        let mut line = Vec::new();
+        let mut start = 0;
+
+        for (range, token) in tokens {
+            match token {
+                Token::LineBreak(_) => {
+                    lines.push(Line::new(start..range.start, std::mem::take(&mut line)));
+                    start = range.end;
+                }
+                token => line.push((range, token)),
+            }
+        }
+
+        if start < text.len() {
+            lines.push(Line::new(start..text.len(), line));
+        }
+
+        Ok(Self { text, lines })
+    }
+
+    fn first_line(&self, lines: Range<usize>) -> Option<&Line<'a>> {
+        self.lines[lines].iter().find(|line| !line.is_blank())
+    }
+
+    fn locate(&mut self, path: &Path) -> Result<Lookup, Error> {
+        let mut region = Region {
+            lines: 0..self.lines.len(),
+            indent: self
+                .first_line(0..self.lines.len())
+                .map_or(0, |line| line.indent),
+        };
+        let mut parent = None;
+
+        for (depth, segment) in path.0.iter().enumerate() {
+            let prefix = || Path(path.0[..depth].to_vec()).to_string();
+
+            let node = match self.find(&region, segment, prefix)? {
+                Some(node) => node,
+                None => {
+                    return Ok(Lookup::Missing {
+                        depth,
+                        region,
+                        parent,
+                    })
+                }
+            };
+
+            if depth + 1 == path.0.len() {
+                return Ok(Lookup::Found(node, region));
+            }
+
+            region = self.children(node).ok_or_else(|| {
+                Error::NotACollection(Path(path.0[..=depth].to_vec()).to_string())
+            })?;
+            parent = Some(node);
+        }
+
+        Err(Error::InvalidPath(path.to_string()))
+    }
+
+    /// Finds the entry for a key or the item at an index within a region.
+    fn find(
+        &self,
+        region: &Region,
+        segment: &Segment,
+        prefix: impl Fn() -> String,
+    ) -> Result<Option<Node>, Error> {
+        let is_list = matches!(
+            self.first_line(region.lines.clone()).and_then(Line::first),
+            Some(Token::ListItem(_))
+        );
+
+        let mut lines = region.lines.clone().filter(|&i| {
+            let line = &self.lines[i];
+            !line.is_blank() && line.indent == region.indent
+        });
+
+        let line = match segment {
+            Segment::Key(_) if is_list => return Err(Error::NotACollection(prefix())),
+            Segment::Key(key) => {
+                lines.find(|&i| matches!(self.lines[i].first(), Some(Token::Key(k)) if k.0 == key))
+            }
+            Segment::Index(_) if !is_list && !region.lines.is_empty() => {
+                return Err(Error::NotACollection(prefix()))
+            }
+            Segment::Index(index) => lines
+                .filter(|&i| matches!(self.lines[i].first(), Some(Token::ListItem(_))))
+                .nth(*index),
+        };
+
+        Ok(line.map(|line| Node {
+            line,
+            end: self.node_end(line, region.indent).min(region.lines.end),
+            indent: region.indent,
+        }))
+    }
+
+    /// Returns the index after the last line of the node starting on `line`, which includes the
+    /// lines indented further and, for a key without a value, a list at the same indentation.
+    fn node_end(&self, line: usize, indent: usize) -> usize {
+        let start = &self.lines[line];
+        let lines = (line + 1..self.lines.len()).filter(|&i| !self.lines[i].is_blank());
+
+        let shared_list = matches!(start.first(), Some(Token::Key(_)))
+            && start.value().is_empty()
+            && lines.clone().next().is_some_and(|i| {
+                let next = &self.lines[i];
+                next.indent == indent && matches!(next.first(), Some(Token::ListItem(_)))
+            });
+
+        lines
+            .take_while(|&i| {
+                let next = &self.lines[i];
+                next.indent > indent
+                    || (shared_list
+                        && next.indent == indent
+                        && matches!(next.first(), Some(Token::ListItem(_))))
+            })
+            .last()
+            .map_or(line + 1, |i| i + 1)
+    }
+
+    /// Returns the map or list nested in a node, or `None` if the node has a scalar value.
+    fn children(&mut self, node: Node) -> Option<Region> {
+        let line = &mut self.lines[node.line];
+        let value_start = line.value_start();
+
+        // a map may start on the same line as its list item, so the line is treated as the
+        // first key of a map indented past the `- `
+        if matches!(line.first(), Some(Token::ListItem(_)))
+            && matches!(line.tokens.get(value_start), Some((_, Token::Key(_))))
+        {
+            line.tokens.drain(..value_start);
+            line.indent += 1;
+            line.item = true;
+
+            return Some(Region {
+                lines: node.line..node.end,
+                indent: node.indent + 1,
+            });
+        }
+
+        if !line.value().is_empty() {
+            return None;
+        }
+
+        let lines = node.line + 1..node.end;
+        let indent = self
+            .first_line(lines.clone())
+            .map_or(node.indent + 1, |line| line.indent);
+
+        Some(Region { lines, indent })
+    }
+
+    fn replace(&self, node: Node, source: &str) -> Vec<Edit> {
+        let line = &self.lines[node.line];
+        let value = line.value();
+
+        let nested = matches!(line.first(), Some(Token::ListItem(_)))
+            && matches!(value.first(), Some((_, Token::Key(_))));
+
+        if let (false, Some((first, _)), Some((last, _))) = (nested, value.first(), value.last()) {
+            let end = last.start + self.text[last.clone()].trim_end().len();
+            return vec![(first.start..end, source.to_string())];
+        }
+
+        // the value is nested below the line, it's moved onto the line after the key or `- `
+        let prefix = &line.tokens[line.value_start() - 1].0;
+        let start = prefix.start + self.text[prefix.clone()].trim_end().len();
+
+        let (end, replacement) = match &line.comment {
+            Some(comment) => (comment.start, format!(" {} ", source)),
+            None => (line.range.end, format!(" {}", source)),
+        };
+
+        let mut edits = vec![(start..end, replacement)];
+
+        if node.end > node.line + 1 {
+            edits.push((
+                line.range.end..self.lines[node.end - 1].range.end,
+                String::new(),
+            ));
+        }
+
+        edits
+    }
+
+    fn insert(
+        &self,
+        path: &Path,
+        depth: usize,
+        region: Region,
+        parent: Option<Node>,
+        source: &str,
+    ) -> Result<Vec<Edit>, Error> {
+        let keys = path.0[depth..]
+            .iter()
+            .map(|segment| match segment {
+                Segment::Key(key) => Ok(key.as_str()),
+                Segment::Index(_) => Err(Error::NotFound(path.to_string())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let last = region
+            .lines
+            .clone()
+            .rev()
+            .find(|&i| !self.lines[i].is_blank());
+
+        let (offset, mut text) = match (last, parent) {
+            (Some(line), _) => (self.lines[line].range.end, String::from("\n")),
+            (None, Some(node)) => (self.lines[node.line].range.end, String::from("\n")),
+            (None, None) if self.text.is_empty() || self.text.ends_with('\n') => {
+                (self.text.len(), String::new())
+            }
+            (None, None) => (self.text.len(), String::from("\n")),
+        };
+
+        for (i, key) in keys.iter().enumerate() {
+            if i > 0 {
+                text.push('\n');
+            }
+
+            text.push_str(&"  ".repeat(region.indent + i));
+            text.push_str(key);
+            text.push(':');
+        }
+
+        text.push(' ');
+        text.push_str(source);
+
+        if last.is_none() && parent.is_none() {
+            text.push('\n');
+        }
+
+        Ok(vec![(offset..offset, text)])
+    }
+
+    fn remove(&self, node: Node, region: Region) -> Vec<Edit> {
+        let line = &self.lines[node.line];
+
+        if line.item {
+            // the first key of an item's map shares its line with the `- `, so the next key is
+            // moved onto that line
+            let start = line.tokens[0].0.start;
+
+            let next = (node.end..region.lines.end).find(|&i| {
+                let next = &self.lines[i];
+                !next.is_blank() && next.indent == node.indent
+            });
+
+            return match next {
+                Some(next) => vec![(start..self.lines[next].tokens[0].0.start, String::new())],
+                None => vec![(start..self.lines[node.end - 1].range.end, "{}".to_string())],
+            };
+        }
+
+        let end = self
+            .lines
+            .get(node.end)
+            .map_or(self.text.len(), |next| next.range.start);
+
+        vec![(line.range.start..end, String::new())]
+    }
+}
+
+#[cfg(test)]
+mod test_editor {
+    use super::super::Text;
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_get() {
+        let editor = Editor::new(indoc! {"
+            title: Post
+            authors:
+              - name: One
+              - name: Two
+        "})
+        .unwrap();
+
+        assert_eq!(
+            editor.get("title").unwrap(),
+            Some(Value::Text(Text("Post")))
+        );
+        assert_eq!(
+            editor.get("authors[1].name").unwrap(),
+            Some(Value::Text(Text("Two")))
+        );
+        assert_eq!(editor.get("missing").unwrap(), None);
+        assert_eq!(
+            editor.get("authors[").unwrap_err(),
+            Error::InvalidPath("authors[".to_string())
+        );
+    }
+
+    #[test]
+    fn test_set_existing() {
+        let mut editor = Editor::new(indoc! {"
+            # post metadata
+            title: Post # the title
+            lastmod: 2023-01-01   # updated by ci
+
+            author:
+              name: One
+              url: https://example.com
+            authors:
+              - name: One
+                url: one.com
+        "})
+        .unwrap();
+
+        editor.set("lastmod", "2024-06-01").unwrap();
+        editor.set("author.name", "Jane: Doe").unwrap();
+        editor.set("authors[0].name", "Two").unwrap();
+        editor.set("authors[0].url", "two.com").unwrap();
+
+        assert_eq!(
+            editor.as_str(),
+            indoc! {r#"
+                # post metadata
+                title: Post # the title
+                lastmod: 2024-06-01   # updated by ci
+
+                author:
+                  name: "Jane: Doe"
+                  url: https://example.com
+                authors:
+                  - name: Two
+                    url: two.com
+            "#}
+        );
+
+        editor.set_raw("author", "{name: Jane}").unwrap();
+
+        assert_eq!(
+            editor.as_str(),
+            indoc! {r#"
+                # post metadata
+                title: Post # the title
+                lastmod: 2024-06-01   # updated by ci
+
+                author: {name: Jane}
+                authors:
+                  - name: Two
+                    url: two.com
+            "#}
+        );
+    }
+
+    #[test]
+    fn test_set_missing() {
+        let mut editor = Editor::new(indoc! {"
+            title: Post
+            author:
+              name: One
+            tags:
+            - rust
+        "})
+        .unwrap();
+
+        editor.set("author.url", "one.com").unwrap();
+        editor.set_raw("draft", "true").unwrap();
+        editor.set("seo.meta.description", "about\nthings").unwrap();
+
+        assert_eq!(
+            editor.as_str(),
+            indoc! {r#"
+                title: Post
+                author:
+                  name: One
+                  url: one.com
+                tags:
+                - rust
+                draft: true
+                seo:
+                  meta:
+                    description: "about\nthings"
+            "#}
+        );
+
+        let mut editor = Editor::new("").unwrap();
+        editor.set("title", "Post").unwrap();
+        assert_eq!(editor.as_str(), "title: Post\n");
+
+        let mut editor = Editor::new("author:\n").unwrap();
+        editor.set("author.name", "One").unwrap();
+        assert_eq!(editor.as_str(), "author:\n  name: One\n");
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut editor = Editor::new(indoc! {"
+            title: Post
+            author: # to remove
+              name: One
+            authors:
+              - name: One
+                url: one.com
+              - name: Two
+            tags: [a, b]
+        "})
+        .unwrap();
+
+        assert!(editor.remove("author").unwrap());
+        assert!(editor.remove("authors[0].name").unwrap());
+        assert!(editor.remove("authors[1]").unwrap());
+        assert!(!editor.remove("missing").unwrap());
+
+        assert_eq!(
+            editor.as_str(),
+            indoc! {"
+                title: Post
+                authors:
+                  - url: one.com
+                tags: [a, b]
+            "}
+        );
+
+        assert!(editor.remove("authors[0].url").unwrap());
+        assert_eq!(
+            editor.as_str(),
+            indoc! {"
+                title: Post
+                authors:
+                  - {}
+                tags: [a, b]
+            "}
+        );
+    }
+
+    #[test]
+    fn test_invalid_edits() {
+        let mut editor = Editor::new("title: Post\ntags: [a, b]\n").unwrap();
+
+        assert_eq!(
+            editor.set("title.text", "x").unwrap_err(),
+            Error::NotACollection("title".to_string())
+        );
+        assert_eq!(
+            editor.set("tags[0]", "x").unwrap_err(),
+            Error::NotACollection("tags".to_string())
+        );
+        assert_eq!(
+            editor.set_raw("title", "\"unclosed").unwrap_err(),
+            Error::Tokenize
+        );
+        assert_eq!(editor.as_str(), "title: Post\ntags: [a, b]\n");
+    }
+
+    #[test]
+    fn test_scalar() {
+        assert_eq!(scalar("plain text"), "plain text");
+        assert_eq!(scalar("C# tips"), "C# tips");
+        assert_eq!(scalar("a: b"), "\"a: b\"");
+        assert_eq!(scalar("- item"), "\"- item\"");
+        assert_eq!(scalar(" padded"), "\" padded\"");
+        assert_eq!(scalar("say \"hi\""), "say \"hi\"");
+        assert_eq!(scalar("\"hi\""), "\"\\\"hi\\\"\"");
+        assert_eq!(scalar(""), "\"\"");
+        assert_eq!(scalar("true"), "\"true\"");
+        assert_eq!(scalar("False"), "\"False\"");
+        assert_eq!(scalar("123"), "\"123\"");
+        assert_eq!(scalar("-1.5e3"), "\"-1.5e3\"");
+        assert_eq!(scalar("0x1F"), "\"0x1F\"");
+        assert_eq!(scalar(".inf"), "\".inf\"");
+        assert_eq!(scalar("null"), "\"null\"");
+        assert_eq!(scalar("~"), "\"~\"");
+        assert_eq!(scalar("v1.2"), "v1.2");
+        assert_eq!(scalar("trueish"), "trueish");
+    }
+}
@@ -0,0 +1,436 @@
+use super::{Document, Map, Path, Segment, Value};
+use crate::parser::Location;
+use std::fmt;
+
+/// The expected type of a value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    Any,
+    String,
+    Number,
+    Boolean,
+    /// A `YYYY-MM-DD` date, optionally followed by a time.
+    Date,
+    List(Box<Kind>),
+    Map(Schema),
+}
+
+impl Kind {
+    pub fn list(item: Kind) -> Self {
+        Kind::List(Box::new(item))
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Kind::Any => "any value",
+            Kind::String => "string",
+            Kind::Number => "number",
+            Kind::Boolean => "boolean",
+            Kind::Date => "date",
+            Kind::List(_) => "list",
+            Kind::Map(_) => "map",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub key: String,
+    pub kind: Kind,
+    pub required: bool,
+}
+
+/// A declarative description of the keys a frontmatter map is expected to have.
+///
+/// # Example
+/// ```
+/// use mdx_rs::parser::frontmatter::schema::{Kind, Schema};
+///
+/// let schema = Schema::new()
+///     .required("title", Kind::String)
+///     .required("date", Kind::Date)
+///     .required("tags", Kind::list(Kind::String))
+///     .optional("draft", Kind::Boolean);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    pub fields: Vec<Field>,
+    /// Whether keys that aren't part of the schema are reported.
+    pub deny_unknown_keys: bool,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn required(mut self, key: &str, kind: Kind) -> Self {
+        self.fields.push(Field {
+            key: key.to_string(),
+            kind,
+            required: true,
+        });
+        self
+    }
+
+    pub fn optional(mut self, key: &str, kind: Kind) -> Self {
+        self.fields.push(Field {
+            key: key.to_string(),
+            kind,
+            required: false,
+        });
+        self
+    }
+
+    pub fn deny_unknown_keys(mut self) -> Self {
+        self.deny_unknown_keys = true;
+        self
+    }
+
+    /// Validates a document parsed from `source`, returning every violation found.
+    pub fn validate<'a>(&self, document: &Document<'a>, source: &'a str) -> Vec<Violation> {
+        let mut validator = Validator {
+            source,
+            path: Path::default(),
+            violations: Vec::new(),
+        };
+
+        match &document.0 {
+            // an empty document is validated as an empty map so missing keys are reported
+            Value::Null => validator.map(self, &Map(Vec::new()), None),
+            value => validator.value(&Kind::Map(self.clone()), value, None),
+        }
+
+        validator.violations
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ViolationKind {
+    #[error("missing required key")]
+    Missing,
+    #[error("unknown key")]
+    Unknown,
+    #[error("expected {expected}, found {found}")]
+    Type {
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+/// A value that doesn't match the schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub path: Path,
+    /// Where the value, or the map missing a key, starts in the source.
+    pub location: Option<Location>,
+    pub kind: ViolationKind,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.kind)?;
+
+        if let Some(location) = self.location {
+            write!(f, " at {}", location)?;
+        }
+
+        Ok(())
+    }
+}
+
+struct Validator<'a> {
+    source: &'a str,
+    path: Path,
+    violations: Vec<Violation>,
+}
+
+impl<'a> Validator<'a> {
+    fn report(&mut self, span: Option<&'a str>, kind: ViolationKind) {
+        self.violations.push(Violation {
+            path: self.path.clone(),
+            location: span.and_then(|span| Location::of(self.source, span)),
+            kind,
+        });
+    }
+
+    /// Validates a value, `fallback` locates values without a span of their own such as nulls.
+    fn value(&mut self, kind: &Kind, value: &Value<'a>, fallback: Option<&'a str>) {
+        let span = value.span().or(fallback);
+
+        let valid = match (kind, value) {
+            (Kind::Any, _) => true,
+            (Kind::List(item), Value::List(list)) => {
+                for (i, value) in list.0.iter().enumerate() {
+                    self.path.0.push(Segment::Index(i));
+                    self.value(item, value, span);
+                    self.path.0.pop();
+                }
+
+                true
+            }
+            (Kind::Map(schema), Value::Map(map)) => {
+                self.map(schema, map, span);
+                true
+            }
+            (Kind::String, value) => type_name(value) == "string",
+            (Kind::Number, value) => value.as_f64().is_some(),
+            (Kind::Boolean, value) => value.as_bool().is_some(),
+            (Kind::Date, value) => match value {
+                Value::Text(_) | Value::Quoted(_) => value.as_str().is_some_and(|s| is_date(&s)),
+                _ => false,
+            },
+            _ => false,
+        };
+
+        if !valid {
+            self.report(
+                span,
+                ViolationKind::Type {
+                    expected: kind.name(),
+                    found: type_name(value),
+                },
+            );
+        }
+    }
+
+    fn map(&mut self, schema: &Schema, map: &Map<'a>, span: Option<&'a str>) {
+        for field in &schema.fields {
+            self.path.0.push(Segment::Key(field.key.clone()));
+
+            match map.0.iter().find(|(key, _)| key.0 == field.key) {
+                Some((key, value)) => self.value(&field.kind, value, Some(key.0)),
+                None if field.required => self.report(span, ViolationKind::Missing),
+                None => {}
+            }
+
+            self.path.0.pop();
+        }
+
+        if schema.deny_unknown_keys {
+            for (key, _) in &map.0 {
+                if !schema.fields.iter().any(|field| field.key == key.0) {
+                    self.path.0.push(Segment::Key(key.0.to_string()));
+                    self.report(Some(key.0), ViolationKind::Unknown);
+                    self.path.0.pop();
+                }
+            }
+        }
+    }
+}
+
+/// Returns the name of the type of a value, plain scalars are typed by their contents.
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::List(_) => "list",
+        Value::Map(_) => "map",
+        value if value.is_null() => "null",
+        value if value.as_bool().is_some() => "boolean",
+        value if value.as_f64().is_some() => "number",
+        _ => "string",
+    }
+}
+
+/// Whether the text is a `YYYY-MM-DD` date, optionally followed by a time such as `T10:30:00Z`
+/// or ` 10:30:00 +02:00`.
+fn is_date(text: &str) -> bool {
+    let (date, time) = match text.find(['T', 't', ' ']) {
+        Some(i) => (&text[..i], Some(text[i + 1..].trim_start())),
+        None => (text, None),
+    };
+
+    let parts: Vec<&str> = date.split('-').collect();
+
+    let [year, month, day] = parts[..] else {
+        return false;
+    };
+
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return false;
+    }
+
+    let (Ok(year), Ok(month), Ok(day)) = (year.parse::<u32>(), month.parse(), day.parse()) else {
+        return false;
+    };
+
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+
+    (1..=days).contains(&day) && time.is_none_or(is_time)
+}
+
+/// Whether the text is a `HH:MM[:SS[.fraction]]` time with an optional `Z` or `+HH:MM` offset.
+fn is_time(text: &str) -> bool {
+    let end = text
+        .find(|c: char| !(c.is_ascii_digit() || c == ':' || c == '.'))
+        .unwrap_or(text.len());
+    let (clock, zone) = (&text[..end], text[end..].trim_start());
+
+    let (clock, fraction) = match clock.split_once('.') {
+        Some((clock, fraction)) => (clock, Some(fraction)),
+        None => (clock, None),
+    };
+
+    let parts: Vec<&str> = clock.split(':').collect();
+
+    let valid_clock = match parts[..] {
+        [hours, minutes] if fraction.is_none() => is_number(hours, 24) && is_number(minutes, 60),
+        [hours, minutes, seconds] => {
+            is_number(hours, 24) && is_number(minutes, 60) && is_number(seconds, 61)
+        }
+        _ => false,
+    } && fraction
+        .is_none_or(|f| !f.is_empty() && f.bytes().all(|b| b.is_ascii_digit()));
+
+    let valid_zone = match zone {
+        "" | "Z" | "z" => true,
+        zone if zone.starts_with(['+', '-']) => {
+            let (hours, minutes) = zone[1..].split_once(':').unwrap_or((&zone[1..], "00"));
+            is_number(hours, 24) && is_number(minutes, 60)
+        }
+        _ => false,
+    };
+
+    valid_clock && valid_zone
+}
+
+/// Whether the text is a one or two digit number less than `max`.
+fn is_number(text: &str, max: u32) -> bool {
+    (1..=2).contains(&text.len())
+        && text.bytes().all(|b| b.is_ascii_digit())
+        && text.parse::<u32>().is_ok_and(|n| n < max)
+}
+
+#[cfg(test)]
+mod test_schema {
+    use super::super::Tokens;
+    use super::*;
+    use crate::parser::Parse;
+    use indoc::indoc;
+
+    fn post() -> Schema {
+        Schema::new()
+            .required("title", Kind::String)
+            .required("date", Kind::Date)
+            .required("tags", Kind::list(Kind::String))
+            .optional("draft", Kind::Boolean)
+    }
+
+    #[test]
+    fn test_valid_document() {
+        let input = indoc! {r#"
+            title: "Hello: world"
+            date: 2024-02-29
+            tags: [rust, mdx]
+            draft: false
+        "#};
+
+        let (_, tokens) = Tokens::parse(input).unwrap();
+        let document = Document::from_tokens(&tokens).unwrap();
+
+        assert_eq!(post().validate(&document, input), vec![]);
+    }
+
+    #[test]
+    fn test_violations() {
+        let input = indoc! {"
+            date: 2023-02-29
+            tags:
+              - rust
+              - 42
+            draft: maybe
+            extra: value
+        "};
+
+        let (_, tokens) = Tokens::parse(input).unwrap();
+        let document = Document::from_tokens(&tokens).unwrap();
+
+        let violations = post().deny_unknown_keys().validate(&document, input);
+
+        let messages: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+
+        assert_eq!(
+            messages,
+            vec![
+                "title: missing required key at 1:1",
+                "date: expected date, found string at 1:7",
+                "tags[1]: expected string, found number at 4:5",
+                "draft: expected boolean, found string at 5:8",
+                "extra: unknown key at 6:1",
+            ]
+        );
+
+        assert_eq!(
+            violations[2].path,
+            Path(vec![Segment::Key("tags".to_string()), Segment::Index(1)])
+        );
+    }
+
+    #[test]
+    fn test_nested_schema() {
+        let schema = Schema::new().required(
+            "author",
+            Kind::Map(
+                Schema::new()
+                    .required("name", Kind::String)
+                    .optional("age", Kind::Number),
+            ),
+        );
+
+        let input = indoc! {"
+            author:
+              age: forty
+        "};
+
+        let (_, tokens) = Tokens::parse(input).unwrap();
+        let document = Document::from_tokens(&tokens).unwrap();
+
+        let messages: Vec<String> = schema
+            .validate(&document, input)
+            .iter()
+            .map(|v| v.to_string())
+            .collect();
+
+        assert_eq!(
+            messages,
+            vec![
+                "author.name: missing required key at 2:3",
+                "author.age: expected number, found string at 2:8",
+            ]
+        );
+
+        let (_, tokens) = Tokens::parse("author:\n").unwrap();
+        let document = Document::from_tokens(&tokens).unwrap();
+
+        assert_eq!(
+            schema.validate(&document, "author:\n")[0].to_string(),
+            "author: expected map, found null at 1:1"
+        );
+
+        let document = Document(Value::Null);
+
+        assert_eq!(
+            schema.validate(&document, "")[0].kind,
+            ViolationKind::Missing
+        );
+    }
+
+    #[test]
+    fn test_is_date() {
+        assert!(is_date("2024-01-31"));
+        assert!(is_date("2024-01-31T10:30:00Z"));
+        assert!(is_date("2024-01-31 10:30:00.5 +02:00"));
+        assert!(is_date("2024-01-31t10:30"));
+        assert!(!is_date("2024-1-31"));
+        assert!(!is_date("2024-04-31"));
+        assert!(!is_date("2024-01-31T25:00"));
+        assert!(!is_date("yesterday"));
+    }
+}
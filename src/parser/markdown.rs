@@ -1,15 +1,33 @@
-use std::ops::Index;
+pub mod autolink;
+pub mod entity;
+pub mod footnote;
+pub mod html;
+pub mod jsx;
+pub mod reference;
+pub mod slug;
+pub mod toc;
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::{Index, Range, RangeInclusive};
 
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_till1, take_until, take_until1, take_while1},
-    character::complete::{digit1, line_ending, not_line_ending},
-    combinator::{all_consuming, eof},
+    bytes::complete::{tag, take_till, take_till1, take_until, take_while1, take_while_m_n},
+    character::complete::{
+        char, digit1, line_ending, multispace0, multispace1, not_line_ending, one_of, space0,
+        space1,
+    },
+    combinator::{all_consuming, eof, map, not, opt, peek, recognize, success, value, verify},
+    error::{Error, ErrorKind},
     multi::{many0, many1, many_till},
+    sequence::{delimited, pair, preceded, terminated, tuple},
     IResult,
 };
 
 use super::Parse;
+use slug::{slugify, Slugger};
 
 /// Parses a line of test, discarding the new line sequence and returning the line and remaining
 /// text.
@@ -19,680 +37,4251 @@ pub fn parse_line(input: &str) -> IResult<&str, &str> {
     Ok((rest, line))
 }
 
+/// Parses a line of text that may be the last line of the input and have no new line sequence.
+fn parse_last_line(input: &str) -> IResult<&str, &str> {
+    terminated(not_line_ending, alt((line_ending, eof)))(input)
+}
+
+/// Parses up to three spaces of indentation, more than that makes an indented code block.
+fn parse_indentation(input: &str) -> IResult<&str, &str> {
+    take_while_m_n(0, 3, |c| c == ' ')(input)
+}
+
+/// Returns a recoverable error, allowing an `alt` to try the next parser.
+fn fail<T>(input: &str) -> IResult<&str, T> {
+    Err(nom::Err::Error(Error::new(input, ErrorKind::Verify)))
+}
+
+/// Whether the line starts a block that can interrupt a paragraph.
+fn interrupts_paragraph(line: &str, options: &Options) -> bool {
+    let Ok((rest, _)) = parse_indentation(line) else {
+        return false;
+    };
+
+    // a list item only interrupts a paragraph when it isn't empty, and an ordered one only when
+    // it starts at 1, so a line such as `1984. A good year.` continues the paragraph
+    let list_item = |input| -> IResult<&str, &str> {
+        let ordered = terminated(
+            verify(digit1, |number: &str| number.parse() == Ok(1u32)),
+            one_of(".)"),
+        );
+        let (rest, _) = alt((recognize(one_of("-*+")), recognize(ordered)))(input)?;
+        let (rest, _) = space1(rest)?;
+        verify(parse_last_line, |content: &str| !content.trim().is_empty())(rest)
+    };
+
+    Heading::parse_atx(line).is_ok()
+        || ThematicBreak::parse(line).is_ok()
+        || rest.starts_with("```")
+        || rest.starts_with("~~~")
+        || rest.starts_with('>')
+        || list_item(rest).is_ok()
+        || (options.mode == Mode::Markdown && html::interrupts_paragraph(line))
+}
+
+/// How `<` and `{` are read, MDX replaces raw HTML with JSX.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Mode {
+    /// Raw HTML blocks, inline HTML and comments are kept as they are.
+    #[default]
+    Markdown,
+    /// JSX tags and `{expressions}` are kept as they are, while HTML comments and `<...>`
+    /// autolinks aren't recognized.
+    Mdx,
+}
+
+/// Enables extensions to the markdown syntax, which are all disabled by default.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Options {
+    pub mode: Mode,
+    /// `~~strikethrough~~` text, as in GFM.
+    pub strikethrough: bool,
+    /// `==highlighted==` text.
+    pub highlight: bool,
+    /// `^superscript^` text, which can't contain whitespace.
+    pub superscript: bool,
+    /// `~subscript~` text, which can't contain whitespace.
+    pub subscript: bool,
+    /// `Term` lines followed by `: Definition` lines, as in PHP Markdown Extra.
+    pub definition_lists: bool,
+    /// Inline `$math$` and display `$$math$$`, written in LaTeX.
+    pub math: bool,
+    /// `:::note` container directives and GitHub's `> [!NOTE]` alerts.
+    pub admonitions: bool,
+}
 
 #[derive(Debug, PartialEq)]
 pub struct Heading<'a> {
     pub level: u8,
     pub text: &'a str,
+    pub id: HeadingId<'a>,
 }
 
-impl<'a> Parse<'a> for Heading<'a> {
-    fn parse(input: &'a str) -> IResult<&str, Self> {
-        fn parse_level(i: &str) -> IResult<&str, u8> {
-            let (rest, level): (&str, &str) = take_while1(|c: char| c == '#')(i)?;
-            let level: usize = level.len();
-            let level = level as u8;
-            Ok((rest, level))
+/// The anchor id of a heading.
+#[derive(Debug, PartialEq)]
+pub enum HeadingId<'a> {
+    /// An id set explicitly with a `{#id}` attribute.
+    Custom(&'a str),
+    /// A slug generated from the text, made unique across a document by [`Block::parse`].
+    Slug(String),
+}
+
+impl<'a> HeadingId<'a> {
+    pub fn as_str(&self) -> &str {
+        match self {
+            HeadingId::Custom(id) => id,
+            HeadingId::Slug(slug) => slug,
         }
+    }
+}
+
+impl<'a> fmt::Display for HeadingId<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<'a> Parse<'a> for Heading<'a> {
+    /// Parses an ATX heading (`## Title`) or a setext heading, which is a paragraph underlined
+    /// with `=` for level 1 or `-` for level 2.
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        Self::parse_with(input, &Options::default())
+    }
+}
+
+impl<'a> Heading<'a> {
+    /// Parses a heading, the options decide which lines end the text of a setext heading.
+    pub fn parse_with(input: &'a str, options: &Options) -> IResult<&'a str, Self> {
+        alt((Self::parse_atx, |i| Self::parse_setext(i, options)))(input)
+    }
+
+    fn parse_atx(input: &'a str) -> IResult<&'a str, Self> {
+        let (rest, _) = parse_indentation(input)?;
 
-        // get the "level" of the heading
-        let (rest, level) = parse_level(input)?;
-        let (rest, text) = parse_line(rest)?;
+        // get the "level" of the heading, which can't be deeper than 6
+        let (rest, level) = verify(take_while1(|c: char| c == '#'), |level: &str| {
+            level.len() <= 6
+        })(rest)?;
+
+        // the opening sequence must be followed by whitespace unless the heading is empty
+        let (rest, _) = peek(alt((space1, line_ending, eof)))(rest)?;
+        let (rest, text) = parse_last_line(rest)?;
+
+        let (text, id) = split_custom_id(text.trim());
 
         // return the heading with the remaining text
         Ok((
             rest,
-            Heading {
-                level,
-                text: text.trim(),
-            },
+            Heading::new(level.len() as u8, strip_closing_sequence(text), id),
         ))
     }
-}
 
-#[derive(Debug, PartialEq)]
-pub struct CodeBlock<'a> {
-    pub lang: Option<&'a str>,
-    pub contents: &'a str,
-}
+    fn new(level: u8, text: &'a str, id: Option<&'a str>) -> Self {
+        let id = match id {
+            Some(id) => HeadingId::Custom(id),
+            None => HeadingId::Slug(slugify(&plain_text(text))),
+        };
 
-impl<'a> Parse<'a> for CodeBlock<'a> {
-    fn parse(input: &'a str) -> IResult<&str, Self> {
-        fn parse_start(input: &str) -> IResult<&str, Option<&str>> {
-            let (rest, _) = tag("```")(input)?;
+        Self { level, text, id }
+    }
 
-            let (rest, lang) = parse_line(rest)?;
-            let lang = lang.trim();
+    /// Returns the text with inline markdown flattened to plain text and escapes decoded.
+    pub fn text_value(&self) -> String {
+        plain_text(self.text)
+    }
 
-            let lang = match lang.len() {
-                0 => None,
-                _ => Some(lang),
-            };
+    /// Returns the text flattened to plain text, with the extension spans enabled in `options`
+    /// flattened as well.
+    pub fn text_value_with(&self, options: &Options) -> String {
+        plain_text_with(self.text, options)
+    }
 
-            Ok((rest, lang))
-        }
+    fn parse_setext(input: &'a str, options: &Options) -> IResult<&'a str, Self> {
+        let mut rest = input;
+        let mut end = 0;
 
-        fn parse_content(input: &str) -> IResult<&str, &str> {
-            let (rest, content) = take_until("```")(input)?;
-            // get rid of the new line
-            let (_, content) = not_line_ending(content)?;
-            // get rid of the closing ```
-            let (rest, _) = tag("```")(rest)?;
-            Ok((rest, content))
-        }
+        loop {
+            let (next, line) = parse_last_line(rest)?;
 
-        let (rest, lang) = parse_start(input)?;
-        let (rest, contents) = parse_content(rest)?;
+            if end > 0 {
+                if let Some(level) = setext_level(line) {
+                    let (text, id) = split_custom_id(input[..end].trim());
+                    return Ok((next, Heading::new(level, text, id)));
+                }
+            }
 
-        Ok((rest, CodeBlock { lang, contents }))
-    }
-}
+            // an indented first line is code rather than the text of a heading
+            let code = end == 0 && strip_columns(line, 4).1 == 4;
 
-#[derive(Debug, PartialEq)]
-pub struct Link<'a> {
-    pub text: &'a str,
-    pub url: &'a str,
-}
+            if code || line.trim().is_empty() || interrupts_paragraph(line, options) {
+                return fail(input);
+            }
 
-impl<'a> Parse<'a> for Link<'a> {
-    fn parse(input: &'a str) -> IResult<&str, Self> {
-        // parse the text
-        let (rest, _) = tag("[")(input)?;
-        let (rest, text) = take_until("]")(rest)?;
-        let (rest, _) = tag("]")(rest)?;
-        // parse the url
-        let (rest, _) = tag("(")(rest)?;
-        let (rest, url) = take_until(")")(rest)?;
-        let (rest, _) = tag(")")(rest)?;
-        Ok((rest, Self { text, url }))
+            end = input.len() - rest.len() + line.len();
+            rest = next;
+        }
     }
 }
 
-impl<'a> Link<'a> {
-    pub fn parse_into_text_block(input: &'a str) -> IResult<&str, TextBlockItem> {
-        let (rest, inner) = Self::parse(input)?;
-        Ok((rest, TextBlockItem::Link(inner)))
+/// Removes the optional closing sequence of `#` from an ATX heading, which must be preceded by
+/// whitespace unless it makes up the whole heading.
+fn strip_closing_sequence(text: &str) -> &str {
+    let stripped = text.trim_end_matches('#');
+
+    if stripped.is_empty() {
+        stripped
+    } else if stripped.ends_with([' ', '\t']) {
+        stripped.trim_end()
+    } else {
+        text
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub struct Image<'a> {
-    pub alt: &'a str,
-    pub source: &'a str,
+/// Splits a trailing `{#id}` attribute from the text of a heading.
+fn split_custom_id(text: &str) -> (&str, Option<&str>) {
+    let Some((before, id)) = text
+        .strip_suffix('}')
+        .and_then(|inner| inner.rsplit_once("{#"))
+    else {
+        return (text, None);
+    };
+
+    let valid_id = !id.is_empty() && !id.contains(|c: char| c.is_whitespace() || c == '{');
+
+    if valid_id && (before.is_empty() || before.ends_with([' ', '\t'])) {
+        (before.trim_end(), Some(id))
+    } else {
+        (text, None)
+    }
 }
 
-impl<'a> Parse<'a> for Image<'a> {
-    fn parse(input: &'a str) -> IResult<&str, Self> {
-        // parse the alt
-        let (rest, _) = tag("![")(input)?;
-        let (rest, alt) = take_until("]")(rest)?;
-        let (rest, _) = tag("]")(rest)?;
-        // parse the source
-        let (rest, _) = tag("(")(rest)?;
-        let (rest, source) = take_until(")")(rest)?;
-        let (rest, _) = tag(")")(rest)?;
-        Ok((rest, Self { alt, source }))
+/// Returns the level of the heading for a setext underline, `=` for level 1 and `-` for level 2.
+fn setext_level(line: &str) -> Option<u8> {
+    let underline = line.trim();
+
+    if line.len() - line.trim_start().len() > 3 || underline.is_empty() {
+        None
+    } else if underline.bytes().all(|b| b == b'=') {
+        Some(1)
+    } else if underline.bytes().all(|b| b == b'-') {
+        Some(2)
+    } else {
+        None
     }
 }
 
 #[derive(Debug, PartialEq)]
-pub struct UnorderedList<'a> {
-    pub items: Vec<&'a str>,
+pub struct CodeBlock<'a> {
+    pub lang: Option<&'a str>,
+    /// The attributes that follow the language in the info string.
+    pub meta: CodeMeta<'a>,
+    pub contents: Cow<'a, str>,
+    /// Whether the code was indented by four spaces rather than fenced.
+    pub indented: bool,
 }
 
-impl<'a> Parse<'a> for UnorderedList<'a> {
-    /// Parse the input into an unordered list.
-    fn parse(input: &'a str) -> IResult<&str, Self> {
-        fn parse_list_item(i: &str) -> IResult<&str, &str> {
-            let (rest, _) = tag("- ")(i)?;
-            let (rest, item) = parse_line(rest)?;
-            Ok((rest, item.trim()))
-        }
-
-        let (rest, items) = many1(parse_list_item)(input)?;
-
-        Ok((rest, UnorderedList { items }))
+impl<'a> Parse<'a> for CodeBlock<'a> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        alt((Self::parse_fenced, Self::parse_indented))(input)
     }
 }
 
-impl<'a> UnorderedList<'a> {
-    pub fn len(&self) -> usize {
-        self.items.len()
+impl<'a> CodeBlock<'a> {
+    /// Parses a code block fenced by at least three backticks or tildes. The closing fence must use
+    /// the same character and be at least as long as the opening one, without one the block runs
+    /// to the end of the input.
+    fn parse_fenced(input: &'a str) -> IResult<&'a str, Self> {
+        Self::parse_fenced_in(input, 0)
     }
-}
 
-impl<'a> Index<usize> for UnorderedList<'a> {
-    type Output = str;
-
-    fn index(&self, index: usize) -> &Self::Output {
-        self.items[index]
+    /// Parses a fenced code block within a container such as a footnote, whose indentation of
+    /// `container` spaces is removed from each line.
+    fn parse_fenced_in(input: &'a str, container: usize) -> IResult<&'a str, Self> {
+        Self::parse_fenced_within(input, |line| {
+            let spaces = line.len() - line.trim_start_matches(' ').len();
+            Some(&line[spaces.min(container)..])
+        })
     }
-}
 
-impl<'a> IntoIterator for UnorderedList<'a> {
-    type Item = &'a str;
-    type IntoIter = <Vec<&'a str> as IntoIterator>::IntoIter;
+    /// Parses a fenced code block within a container, `strip` removes the prefix of the container
+    /// from a line, such as the `>` of a blockquote, and returns `None` for a line outside of it,
+    /// which ends the block.
+    fn parse_fenced_within(
+        input: &'a str,
+        strip: impl Fn(&'a str) -> Option<&'a str>,
+    ) -> IResult<&'a str, Self> {
+        let (start, line) = parse_last_line(input)?;
+
+        let Some(line) = strip(line) else {
+            return fail(input);
+        };
+
+        let (rest, indent) = parse_indentation(line)?;
+        let (info, fence) = parse_fence(rest)?;
+
+        // backticks in the info string of a backtick fence make it inline code instead
+        if fence.starts_with('`') && info.contains('`') {
+            return fail(input);
+        }
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.items.into_iter()
-    }
-}
+        let info = info.trim();
+        let end = info
+            .find(|c: char| c.is_whitespace() || c == '{')
+            .unwrap_or(info.len());
+        let lang = (end > 0).then(|| &info[..end]);
+        let (_, meta) = CodeMeta::parse(&info[end..])?;
 
-#[derive(Debug, PartialEq)]
-pub struct OrderedList<'a> {
-    pub items: Vec<&'a str>,
-}
+        let mut rest = start;
+        let mut end = 0;
+        let mut lines = Vec::new();
+        // whether the lines are whole lines of the input, which can be borrowed
+        let mut whole = indent.is_empty();
 
-impl<'a> Parse<'a> for OrderedList<'a> {
-    /// Parse the input into an ordered list.
-    fn parse(input: &'a str) -> IResult<&str, Self> {
-        fn parse_list_item(i: &str) -> IResult<&str, &str> {
-            let (rest, _) = digit1(i)?;
-            let (rest, _) = tag(". ")(rest)?;
-            let (rest, item) = parse_line(rest)?;
-            Ok((rest, item.trim()))
+        while !rest.is_empty() {
+            let (next, line) = parse_last_line(rest)?;
+
+            let Some(stripped) = strip(line) else {
+                break;
+            };
+
+            if is_closing_fence(stripped, fence) {
+                rest = next;
+                break;
+            }
+
+            let spaces = stripped.len() - stripped.trim_start_matches(' ').len();
+            let code = &stripped[spaces.min(indent.len())..];
+
+            whole &= code.len() == line.len();
+            end = start.len() - rest.len() + line.len();
+            lines.push(code);
+            rest = next;
         }
 
-        let (rest, items) = many1(parse_list_item)(input)?;
+        // the indentation of the opening fence is removed from each line of the contents
+        let contents = match whole {
+            true => Cow::Borrowed(&start[..end]),
+            false => Cow::Owned(lines.join("\n")),
+        };
 
-        Ok((rest, OrderedList { items }))
+        Ok((
+            rest,
+            CodeBlock {
+                lang,
+                meta,
+                contents,
+                indented: false,
+            },
+        ))
     }
-}
 
-impl<'a> OrderedList<'a> {
-    pub fn len(&self) -> usize {
-        self.items.len()
+    /// Parses lines indented by at least four columns, with tabs expanded to multiples of four.
+    /// Blank lines between the lines of code are part of the block.
+    fn parse_indented(input: &'a str) -> IResult<&'a str, Self> {
+        Self::parse_indented_in(input, 0)
     }
-}
 
-impl<'a> IntoIterator for OrderedList<'a> {
-    type Item = &'a str;
-    type IntoIter = <Vec<&'a str> as IntoIterator>::IntoIter;
+    /// Parses an indented code block within a container whose indentation of `container` columns
+    /// is removed from each line along with that of the code.
+    fn parse_indented_in(input: &'a str, container: usize) -> IResult<&'a str, Self> {
+        let indent = container + 4;
+        let mut rest = input;
+        let mut lines = Vec::new();
+        // the number of lines, and the remaining input, up to the last line of code
+        let mut end = (0, input);
+
+        while !rest.is_empty() {
+            let (next, line) = parse_last_line(rest)?;
+            let (code, columns) = strip_columns(line, indent);
+
+            if line.trim().is_empty() && !lines.is_empty() {
+                lines.push(code);
+            } else if columns >= indent {
+                lines.push(code);
+                end = (lines.len(), next);
+            } else {
+                break;
+            }
+
+            rest = next;
+        }
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.items.into_iter()
-    }
-}
+        let (count, rest) = end;
 
-impl<'a> Index<usize> for OrderedList<'a> {
-    type Output = str;
+        if count == 0 {
+            return fail(input);
+        }
 
-    fn index(&self, index: usize) -> &Self::Output {
-        self.items[index]
+        Ok((
+            rest,
+            CodeBlock {
+                lang: None,
+                meta: CodeMeta::default(),
+                contents: Cow::Owned(lines[..count].join("\n")),
+                indented: true,
+            },
+        ))
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub struct Task<'a> {
-    pub text: &'a str,
-    pub completed: bool,
-}
+/// Removes up to `columns` columns of leading whitespace, with tabs expanded to multiples of four,
+/// returning the rest of the line and the number of columns removed.
+fn strip_columns(line: &str, columns: usize) -> (&str, usize) {
+    let mut column = 0;
 
-impl<'a> Parse<'a> for Task<'a> {
-    /// Parse the input as a task. Expects the input to include the leading -.
-    fn parse(input: &'a str) -> IResult<&str, Self> {
-        fn parse_completed(input: &str) -> IResult<&str, bool> {
-            let (rest, _) = tag("- [x] ")(input)?;
-            Ok((rest, true))
+    for (i, c) in line.char_indices() {
+        if column >= columns {
+            return (&line[i..], column);
         }
 
-        fn parse_incomplete(input: &str) -> IResult<&str, bool> {
-            let (rest, _) = tag("- [ ] ")(input)?;
-            Ok((rest, false))
+        match c {
+            ' ' => column += 1,
+            '\t' => column = (column / 4 + 1) * 4,
+            _ => return (&line[i..], column),
         }
-
-        let (rest, completed) = alt((parse_completed, parse_incomplete))(input)?;
-        let (rest, text) = parse_line(rest)?;
-
-        Ok((rest, Self { text, completed }))
     }
-}
 
-#[derive(Debug, PartialEq)]
-pub struct TaskList<'a> {
-    pub tasks: Vec<Task<'a>>,
+    ("", column)
 }
 
-impl<'a> Parse<'a> for TaskList<'a> {
-    fn parse(input: &'a str) -> IResult<&str, Self> {
-        let (rest, tasks) = many1(Task::parse)(input)?;
-        Ok((rest, Self { tasks }))
-    }
+/// Parses the fence that opens a code block, at least three backticks or tildes.
+fn parse_fence(input: &str) -> IResult<&str, &str> {
+    verify(
+        alt((take_while1(|c| c == '`'), take_while1(|c| c == '~'))),
+        |fence: &str| fence.len() >= 3,
+    )(input)
 }
-impl<'a> TaskList<'a> {
-    pub fn len(&self) -> usize {
-        self.tasks.len()
-    }
-}
-
-impl<'a> Index<usize> for TaskList<'a> {
-    type Output = Task<'a>;
 
-    fn index(&self, index: usize) -> &Self::Output {
-        &self.tasks[index]
-    }
-}
+/// Whether the line closes a code block opened with `fence`.
+fn is_closing_fence(line: &str, fence: &str) -> bool {
+    let Ok((rest, _)) = parse_indentation(line) else {
+        return false;
+    };
 
-impl<'a> IntoIterator for TaskList<'a> {
-    type Item = Task<'a>;
-    type IntoIter = <Vec<Task<'a>> as IntoIterator>::IntoIter;
+    let marker = &fence[..1];
+    let closing = rest.trim_start_matches(marker);
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.tasks.into_iter()
-    }
+    rest.len() - closing.len() >= fence.len() && closing.trim().is_empty()
 }
 
-/// A reference to a footnote.
+/// The attributes of a code block's info string.
 ///
 /// # Example
-/// ```markdown
-/// [^note]
-/// ```
-#[derive(Debug, PartialEq)]
-pub struct FootnoteRef<'a> {
-    pub name: &'a str,
+/// ````markdown
+/// ```rust title="main.rs" {1,3-5} showLineNumbers
+/// ````
+#[derive(Debug, Default, PartialEq)]
+pub struct CodeMeta<'a> {
+    /// `key="value"`, `key=value` and bare `flag` attributes, flags have no value.
+    pub attributes: Vec<(&'a str, Option<&'a str>)>,
+    /// The lines to highlight, given as `{1,3-5}`.
+    pub highlight: Vec<RangeInclusive<usize>>,
 }
 
-impl<'a> FootnoteRef<'a> {
-    pub fn parse(input: &'a str) -> IResult<&str, Self> {
-        let (rest, _) = tag("[^")(input)?;
-        let (rest, name) = take_until("]")(rest)?;
-        let (rest, _) = tag("]")(rest)?;
-        Ok((rest, Self { name }))
+impl<'a> CodeMeta<'a> {
+    /// Returns the value of an attribute.
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| *k == key)
+            .and_then(|(_, value)| *value)
     }
 
-    pub fn parse_into_text_block(input: &'a str) -> IResult<&str, TextBlockItem> {
-        let (rest, inner) = Self::parse(input)?;
-        Ok((rest, TextBlockItem::FootnoteRef(inner)))
+    /// Whether an attribute or flag is present.
+    pub fn has(&self, key: &str) -> bool {
+        self.attributes.iter().any(|(k, _)| *k == key)
     }
-}
 
-/// A footnote.
-///
-/// # Example
-/// ```markdown
-/// [^1]: My reference.
-/// ```
-#[derive(Debug, PartialEq)]
-pub struct Footnote<'a> {
-    pub name: &'a str,
-    pub text: Vec<&'a str>,
-}
+    fn parse_attribute(input: &'a str) -> IResult<&'a str, (&'a str, Option<&'a str>)> {
+        let (rest, key) = take_till1(|c: char| c.is_whitespace() || c == '=' || c == '{')(input)?;
 
-impl<'a> Parse<'a> for Footnote<'a> {
-    fn parse(input: &'a str) -> IResult<&str, Self> {
-        fn parse_extra_lines(input: &str) -> IResult<&str, &str> {
-            let (rest, _) = tag("  ")(input)?;
-            let (rest, line) = parse_line(rest)?;
-            Ok((rest, line))
-        }
+        let Ok((rest, _)) = tag::<_, _, Error<&str>>("=")(rest) else {
+            return Ok((rest, (key, None)));
+        };
 
-        let (rest, _) = tag("[^")(input)?;
-        let (rest, name) = take_until("]:")(rest)?;
-        let (rest, _) = tag("]:")(rest)?;
-        let (rest, text) = parse_line(rest)?;
+        let (rest, value) = alt((
+            delimited(tag("\""), take_until("\""), tag("\"")),
+            delimited(tag("'"), take_until("'"), tag("'")),
+            take_till(|c: char| c.is_whitespace()),
+        ))(rest)?;
 
-        let (rest, mut lines) = many0(parse_extra_lines)(rest)?;
+        Ok((rest, (key, Some(value))))
+    }
+
+    fn parse_ranges(input: &'a str) -> IResult<&'a str, Vec<RangeInclusive<usize>>> {
+        let (rest, ranges) = delimited(tag("{"), take_until("}"), tag("}"))(input)?;
 
-        lines.insert(0, text.trim());
+        // ranges that aren't numbers are ignored
+        let ranges = ranges
+            .split(',')
+            .filter_map(|range| {
+                let (start, end) = range.split_once('-').unwrap_or((range, range));
+                Some(start.trim().parse().ok()?..=end.trim().parse().ok()?)
+            })
+            .collect();
 
-        Ok((rest, Self { name, text: lines }))
+        Ok((rest, ranges))
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub struct Text<'a>(pub &'a str);
+impl<'a> Parse<'a> for CodeMeta<'a> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        let mut meta = CodeMeta::default();
+        let mut rest = input.trim_start();
+
+        while let Some(c) = rest.chars().next() {
+            if let Ok((next, ranges)) = Self::parse_ranges(rest) {
+                meta.highlight.extend(ranges);
+                rest = next;
+            } else if let Ok((next, attribute)) = Self::parse_attribute(rest) {
+                meta.attributes.push(attribute);
+                rest = next;
+            } else {
+                // skip stray characters such as an unclosed `{`
+                rest = &rest[c.len_utf8()..];
+            }
+
+            rest = rest.trim_start();
+        }
 
-impl<'a> Text<'a> {
-    pub fn parse_into_text_block(input: &'a str) -> IResult<&str, TextBlockItem> {
-        let (rest, text) = Self::parse(input)?;
-        Ok((rest, TextBlockItem::Text(text)))
+        Ok((rest, meta))
     }
 }
 
-impl<'a> Parse<'a> for Text<'a> {
-    fn parse(input: &'a str) -> IResult<&str, Self> {
-        let (rest, text) = take_till1(|c| c == '`' || c == '[')(input)?;
+/// The label of a reference link or image, which is resolved against a [`Definition`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reference<'a> {
+    /// `[text][label]`
+    Full(&'a str),
+    /// `[label][]`
+    Collapsed,
+    /// `[label]`
+    Shortcut,
+}
 
-        Ok((rest, Self(text)))
+impl<'a> Parse<'a> for Reference<'a> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        alt((
+            value(Reference::Collapsed, tag("[]")),
+            map(parse_label, Reference::Full),
+            success(Reference::Shortcut),
+        ))(input)
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub enum TextBlockItem<'a> {
-    Text(Text<'a>),
-    FootnoteRef(FootnoteRef<'a>),
-    Link(Link<'a>),
+/// Takes the text up to the first of the `ends` characters that isn't escaped with a backslash.
+fn take_unescaped<'a>(ends: &'static [char]) -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
+    move |input| {
+        let mut escaped = false;
+
+        for (i, c) in input.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                c if ends.contains(&c) => return Ok((&input[i..], &input[..i])),
+                _ => {}
+            }
+        }
+
+        fail(input)
+    }
 }
 
-#[derive(Debug, PartialEq)]
-pub struct TextBlock<'a> {
-    pub contents: Vec<TextBlockItem<'a>>,
+/// Parses the label of a reference within square brackets, which can contain escaped brackets
+/// but not unescaped ones.
+fn parse_label(input: &str) -> IResult<&str, &str> {
+    delimited(tag("["), take_unescaped(&['[', ']']), tag("]"))(input)
 }
 
-impl<'a> TextBlock<'a> {
-    pub fn len(&self) -> usize {
-        self.contents.len()
+/// Parses text within square brackets, which may contain balanced or escaped brackets, such as
+/// `[a [b] c]`.
+fn parse_bracketed(input: &str) -> IResult<&str, &str> {
+    let (rest, _) = tag("[")(input)?;
+    let mut depth = 0;
+    let mut escaped = false;
+
+    for (i, c) in rest.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '[' => depth += 1,
+            ']' if depth == 0 => return Ok((&rest[i + 1..], &rest[..i])),
+            ']' => depth -= 1,
+            _ => {}
+        }
     }
-}
 
-impl<'a> Parse<'a> for TextBlock<'a> {
-    fn parse(input: &'a str) -> IResult<&str, Self> {
-        let (rest, contents) = take_until1("\n\n")(input)?;
-        let (rest, _) = many1(tag("\n"))(rest)?;
+    fail(input)
+}
 
-        let (_, contents) = all_consuming(many1(alt((
-            Text::parse_into_text_block,
-            FootnoteRef::parse_into_text_block,
-            Link::parse_into_text_block,
-        ))))(contents)?;
+/// Parses the parenthesized destination and optional title of an inline link or image. The
+/// destination is either within `<...>`, or has no spaces and only balanced parentheses, such as
+/// `https://en.wikipedia.org/wiki/Rust_(language)`.
+fn parse_destination(input: &str) -> IResult<&str, (&str, Option<&str>)> {
+    fn parse_bare(input: &str) -> IResult<&str, &str> {
+        let mut depth = 0;
+        let mut escaped = false;
+
+        for (i, c) in input.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '(' => depth += 1,
+                ')' if depth == 0 => return Ok((&input[i..], &input[..i])),
+                ')' => depth -= 1,
+                c if c.is_whitespace() || c.is_control() => {
+                    return match depth {
+                        0 => Ok((&input[i..], &input[..i])),
+                        _ => fail(input),
+                    }
+                }
+                _ => {}
+            }
+        }
 
-        Ok((rest, Self { contents }))
+        fail(input)
     }
-}
 
-#[derive(Debug, PartialEq)]
-pub struct Newline;
+    let (rest, _) = pair(tag("("), multispace0)(input)?;
+    let (rest, url) = alt((
+        delimited(
+            tag("<"),
+            take_till(|c| matches!(c, '<' | '>' | '\n')),
+            tag(">"),
+        ),
+        parse_bare,
+    ))(rest)?;
+    let (rest, title) = opt(preceded(multispace1, parse_title))(rest)?;
+    let (rest, _) = pair(multispace0, tag(")"))(rest)?;
+
+    Ok((rest, (url, title)))
+}
 
-impl<'a> Parse<'a> for Newline {
-    fn parse(input: &'a str) -> IResult<&str, Self> {
-        let (rest, _) = line_ending(input)?;
-        Ok((rest, Self))
-    }
+/// Parses a link title delimited by double quotes, single quotes or parentheses.
+fn parse_title(input: &str) -> IResult<&str, &str> {
+    alt((
+        delimited(tag("\""), take_unescaped(&['"']), tag("\"")),
+        delimited(tag("'"), take_unescaped(&['\'']), tag("'")),
+        delimited(tag("("), take_unescaped(&['(', ')']), tag(")")),
+    ))(input)
 }
 
 #[derive(Debug, PartialEq)]
-pub enum Block<'a> {
-    Heading(Heading<'a>),
-    CodeBlock(CodeBlock<'a>),
-    Link(Link<'a>),
-    Image(Image<'a>),
-    OrderedList(OrderedList<'a>),
-    UnorderedList(UnorderedList<'a>),
-    TaskList(TaskList<'a>),
-    Footnote(Footnote<'a>),
-    TextBlock(TextBlock<'a>),
-    Newline(Newline),
+pub struct Link<'a> {
+    pub text: &'a str,
+    /// The destination, which is empty for reference links until they are resolved.
+    pub url: &'a str,
+    pub title: Option<&'a str>,
+    /// How a reference link refers to its definition, `None` for inline links.
+    pub reference: Option<Reference<'a>>,
+    /// Whether the link is a URL or email address written as is, see [`Link::href`].
+    pub autolink: bool,
 }
 
-impl<'a> From<Heading<'a>> for Block<'a> {
-    fn from(heading: Heading<'a>) -> Self {
-        Block::Heading(heading)
+impl<'a> Parse<'a> for Link<'a> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        // parse the text, which can't start with `^` as that's a footnote reference
+        let (rest, text) = verify(parse_bracketed, |text: &str| !text.starts_with('^'))(input)?;
+
+        // parse the url
+        if let Ok((rest, (url, title))) = parse_destination(rest) {
+            let link = Self {
+                text,
+                url,
+                title,
+                reference: None,
+                autolink: false,
+            };
+
+            return Ok((rest, link));
+        }
+
+        let (rest, reference) = Reference::parse(rest)?;
+
+        Ok((
+            rest,
+            Self {
+                text,
+                url: "",
+                title: None,
+                reference: Some(reference),
+                autolink: false,
+            },
+        ))
     }
 }
 
-impl<'a> From<CodeBlock<'a>> for Block<'a> {
-    fn from(code_block: CodeBlock<'a>) -> Self {
-        Block::CodeBlock(code_block)
+impl<'a> Link<'a> {
+    pub fn parse_into_text_block(input: &'a str) -> IResult<&'a str, TextBlockItem<'a>> {
+        let (rest, inner) = Self::parse(input)?;
+        Ok((rest, TextBlockItem::Link(inner)))
     }
-}
 
-impl<'a> From<Link<'a>> for Block<'a> {
-    fn from(link: Link<'a>) -> Self {
-        Block::Link(link)
+    /// Returns the label of a reference link.
+    pub fn label(&self) -> Option<&'a str> {
+        match self.reference? {
+            Reference::Full(label) => Some(label),
+            Reference::Collapsed | Reference::Shortcut => Some(self.text),
+        }
     }
-}
 
-impl<'a> From<Image<'a>> for Block<'a> {
-    fn from(image: Image<'a>) -> Self {
-        Block::Image(image)
+    /// Parses an autolink, a URL or email address within `<...>`.
+    pub fn parse_autolink(input: &'a str) -> IResult<&'a str, Self> {
+        map(autolink::parse_autolink, Self::autolink)(input)
     }
-}
 
-impl<'a> From<OrderedList<'a>> for Block<'a> {
-    fn from(ordered_list: OrderedList<'a>) -> Self {
-        Block::OrderedList(ordered_list)
+    /// Parses a bare URL or email address, following the GFM extended autolink rules.
+    pub fn parse_extended_autolink(input: &'a str) -> IResult<&'a str, Self> {
+        map(autolink::parse_extended_autolink, Self::autolink)(input)
     }
-}
 
-impl<'a> From<UnorderedList<'a>> for Block<'a> {
-    fn from(unordered_list: UnorderedList<'a>) -> Self {
-        Block::UnorderedList(unordered_list)
+    fn autolink(url: &'a str) -> Self {
+        Self {
+            text: url,
+            url,
+            title: None,
+            reference: None,
+            autolink: true,
+        }
+    }
+
+    /// Returns the destination of the link with escapes decoded, autolinks to email addresses get
+    /// a `mailto:` scheme and those starting with `www.` get `http://`.
+    pub fn href(&self) -> Cow<'a, str> {
+        let has_scheme = self.url.contains(':');
+
+        match self.autolink {
+            true if self.url.starts_with("www.") => Cow::Owned(format!("http://{}", self.url)),
+            true if !has_scheme && self.url.contains('@') => {
+                Cow::Owned(format!("mailto:{}", self.url))
+            }
+            true => Cow::Borrowed(self.url),
+            false => unescape(self.url),
+        }
+    }
+
+    /// Returns the text with inline markdown flattened to plain text and escapes decoded.
+    pub fn text_value(&self) -> String {
+        plain_text(self.text)
+    }
+
+    /// Returns the text flattened to plain text, with the extension spans enabled in `options`
+    /// flattened as well.
+    pub fn text_value_with(&self, options: &Options) -> String {
+        plain_text_with(self.text, options)
+    }
+
+    /// Returns the title with escapes decoded.
+    pub fn title_value(&self) -> Option<Cow<'a, str>> {
+        self.title.map(unescape)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Image<'a> {
+    pub alt: &'a str,
+    /// The source, which is empty for reference images until they are resolved.
+    pub source: &'a str,
+    pub title: Option<&'a str>,
+    /// How a reference image refers to its definition, `None` for inline images.
+    pub reference: Option<Reference<'a>>,
+}
+
+impl<'a> Parse<'a> for Image<'a> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        // parse the alt
+        let (rest, _) = tag("!")(input)?;
+        let (rest, alt) = parse_bracketed(rest)?;
+
+        // parse the source
+        if let Ok((rest, (source, title))) = parse_destination(rest) {
+            let image = Self {
+                alt,
+                source,
+                title,
+                reference: None,
+            };
+
+            return Ok((rest, image));
+        }
+
+        let (rest, reference) = Reference::parse(rest)?;
+
+        Ok((
+            rest,
+            Self {
+                alt,
+                source: "",
+                title: None,
+                reference: Some(reference),
+            },
+        ))
+    }
+}
+
+impl<'a> Image<'a> {
+    pub fn parse_into_text_block(input: &'a str) -> IResult<&'a str, TextBlockItem<'a>> {
+        let (rest, inner) = Self::parse(input)?;
+        Ok((rest, TextBlockItem::Image(inner)))
+    }
+
+    /// Returns the label of a reference image.
+    pub fn label(&self) -> Option<&'a str> {
+        match self.reference? {
+            Reference::Full(label) => Some(label),
+            Reference::Collapsed | Reference::Shortcut => Some(self.alt),
+        }
+    }
+
+    /// Returns the alt text with inline markdown flattened to plain text and escapes decoded.
+    pub fn alt_value(&self) -> String {
+        plain_text(self.alt)
+    }
+
+    /// Returns the alt text flattened to plain text, with the extension spans enabled in
+    /// `options` flattened as well.
+    pub fn alt_value_with(&self, options: &Options) -> String {
+        plain_text_with(self.alt, options)
+    }
+
+    /// Returns the source with escapes decoded.
+    pub fn source_value(&self) -> Cow<'a, str> {
+        unescape(self.source)
+    }
+
+    /// Returns the title with escapes decoded.
+    pub fn title_value(&self) -> Option<Cow<'a, str>> {
+        self.title.map(unescape)
+    }
+}
+
+/// A link reference definition, which gives the destination of reference links and images.
+/// Labels are matched case-insensitively.
+///
+/// # Example
+/// ```markdown
+/// [docs]: https://docs.rs "Documentation"
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Definition<'a> {
+    pub label: &'a str,
+    pub url: &'a str,
+    pub title: Option<&'a str>,
+}
+
+impl<'a> Parse<'a> for Definition<'a> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        let line_end = |i| pair(space0, alt((line_ending, eof)))(i);
+
+        let (rest, _) = parse_indentation(input)?;
+        let (rest, label) = verify(parse_label, |label: &str| {
+            !label.trim().is_empty() && !label.starts_with('^')
+        })(rest)?;
+        let (rest, _) = tag(":")(rest)?;
+
+        // the destination may be on the next line
+        let (rest, _) = tuple((space0, opt(line_ending), space0))(rest)?;
+        let (rest, url) = alt((
+            delimited(tag("<"), take_till(|c| c == '>' || c == '\n'), tag(">")),
+            take_till1(|c: char| c.is_whitespace()),
+        ))(rest)?;
+
+        // as may the title, if what follows the destination isn't a title it isn't part of the
+        // definition
+        let title = tuple((
+            alt((recognize(tuple((space0, line_ending, space0))), space1)),
+            parse_title,
+            line_end,
+        ))(rest);
+
+        let (rest, title) = match title {
+            Ok((rest, (_, title, _))) => (rest, Some(title)),
+            Err(_) => {
+                let (rest, _) = line_end(rest)?;
+                (rest, None)
+            }
+        };
+
+        Ok((rest, Self { label, url, title }))
+    }
+}
+
+/// An item of an ordered or unordered list.
+#[derive(Debug, PartialEq)]
+pub struct ListItem<'a> {
+    pub text: Cow<'a, str>,
+    /// Whether the `[x]` checkbox of a task is checked, `None` if the item isn't a task.
+    pub checked: Option<bool>,
+    /// The lists nested under the item.
+    pub children: Vec<Block<'a>>,
+}
+
+impl<'a> From<&'a str> for ListItem<'a> {
+    fn from(text: &'a str) -> Self {
+        Self {
+            text: Cow::Borrowed(text),
+            checked: None,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// Parses blank lines, returning whether there were any.
+fn parse_blank_lines(input: &str) -> IResult<&str, bool> {
+    map(many0(pair(space0, line_ending)), |lines| !lines.is_empty())(input)
+}
+
+/// Parses blank lines within a container, `strip` removes the prefix of the container from a
+/// line, such as the `>` of a blockquote, and returns `None` for a line outside of it.
+fn parse_blank_lines_within<'a>(
+    input: &'a str,
+    strip: impl Fn(&'a str) -> Option<&'a str>,
+) -> IResult<&'a str, bool> {
+    let blank = |line| -> IResult<&'a str, &'a str> { terminated(space0, line_ending)(line) };
+    let mut rest = input;
+
+    while let Some(Ok((next, _))) = strip(rest).map(blank) {
+        rest = next;
+    }
+
+    Ok((rest, rest.len() < input.len()))
+}
+
+/// Parses the items of a list, along with the lists nested under each item. The markers of the
+/// items must be indented at least `min_indent` spaces and all by the same amount. Returns the
+/// items and whether the list is loose, with blank lines between its items or their children.
+/// `strip` removes the prefix of a container from each line, like for
+/// [`CodeBlock::parse_fenced_within`].
+fn parse_list_items<'a>(
+    input: &'a str,
+    min_indent: usize,
+    marker: impl Fn(&'a str) -> IResult<&'a str, &'a str> + Copy,
+    strip: impl Fn(&'a str) -> Option<&'a str> + Copy,
+) -> IResult<&'a str, (Vec<ListItem<'a>>, bool)> {
+    let Some(line) = strip(input) else {
+        return fail(input);
+    };
+
+    let indent = line.len() - line.trim_start_matches(' ').len();
+
+    if indent < min_indent || indent > min_indent + 3 {
+        return fail(input);
+    }
+
+    let parse_item = move |input: &'a str| -> IResult<&'a str, (ListItem<'a>, bool)> {
+        let Some(line) = strip(input) else {
+            return fail(input);
+        };
+
+        // a thematic break such as `- - -` ends the list
+        let (rest, _) = not(ThematicBreak::parse)(line)?;
+        let (rest, _) = take_while_m_n(indent, indent, |c| c == ' ')(rest)?;
+        let (rest, marker) = marker(rest)?;
+        let (rest, checked) = opt(terminated(
+            alt((
+                value(false, tag("[ ]")),
+                value(true, tag("[x]")),
+                value(true, tag("[X]")),
+            )),
+            space1,
+        ))(rest)?;
+        let (mut rest, text) = parse_line(rest)?;
+
+        // nested lists start at the column of the text of the item
+        let content = indent + marker.len();
+        let mut children = Vec::new();
+        let mut loose = false;
+
+        loop {
+            let (next, blank) = parse_blank_lines_within(rest, strip)?;
+
+            let Ok((next, child)) = alt((
+                |i| {
+                    UnorderedList::parse_indented_within(i, content, strip)
+                        .map(|(r, l)| (r, Block::from(l)))
+                },
+                |i| {
+                    OrderedList::parse_indented_within(i, content, strip)
+                        .map(|(r, l)| (r, Block::from(l)))
+                },
+            ))(next) else {
+                break;
+            };
+
+            children.push(child);
+            loose |= blank;
+            rest = next;
+        }
+
+        let item = ListItem {
+            text: Cow::Borrowed(text.trim()),
+            checked,
+            children,
+        };
+
+        Ok((rest, (item, loose)))
+    };
+
+    let (mut rest, (item, mut loose)) = parse_item(input)?;
+    let mut items = vec![item];
+
+    // blank lines between items are part of the list if another item follows them
+    loop {
+        let (next, blank) = parse_blank_lines_within(rest, strip)?;
+
+        let Ok((next, (item, item_loose))) = parse_item(next) else {
+            break;
+        };
+
+        items.push(item);
+        loose |= blank || item_loose;
+        rest = next;
+    }
+
+    Ok((rest, (items, loose)))
+}
+
+/// A list of items marked by `-`, `*` or `+`, changing the bullet starts another list.
+#[derive(Debug, PartialEq)]
+pub struct UnorderedList<'a> {
+    pub items: Vec<ListItem<'a>>,
+    pub bullet: char,
+    /// Whether the items are separated by blank lines, which renders them as paragraphs.
+    pub loose: bool,
+}
+
+impl<'a> Parse<'a> for UnorderedList<'a> {
+    /// Parse the input into an unordered list.
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        Self::parse_indented(input, 0)
+    }
+}
+
+impl<'a> UnorderedList<'a> {
+    fn parse_indented(input: &'a str, min_indent: usize) -> IResult<&'a str, Self> {
+        Self::parse_indented_within(input, min_indent, Some)
+    }
+
+    /// Parses a list within a container, `strip` removes the prefix of the container from a
+    /// line and returns `None` for a line outside of it, which ends the list.
+    fn parse_indented_within(
+        input: &'a str,
+        min_indent: usize,
+        strip: impl Fn(&'a str) -> Option<&'a str> + Copy,
+    ) -> IResult<&'a str, Self> {
+        let bullet = match strip(input).and_then(|line| line.trim_start_matches(' ').chars().next())
+        {
+            Some(bullet @ ('-' | '*' | '+')) => bullet,
+            _ => return fail(input),
+        };
+
+        let marker = move |i| recognize(pair(char(bullet), tag(" ")))(i);
+        let (rest, (items, loose)) = parse_list_items(input, min_indent, marker, strip)?;
+
+        Ok((
+            rest,
+            UnorderedList {
+                items,
+                bullet,
+                loose,
+            },
+        ))
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+impl<'a> Index<usize> for UnorderedList<'a> {
+    type Output = str;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.items[index].text
+    }
+}
+
+impl<'a> IntoIterator for UnorderedList<'a> {
+    type Item = ListItem<'a>;
+    type IntoIter = <Vec<ListItem<'a>> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+/// A list of items numbered with up to 9 digits followed by `.` or `)`, changing the delimiter
+/// starts another list.
+#[derive(Debug, PartialEq)]
+pub struct OrderedList<'a> {
+    pub items: Vec<ListItem<'a>>,
+    /// The number of the first item, the numbers of the other items are ignored.
+    pub start: u32,
+    pub delimiter: char,
+    /// Whether the items are separated by blank lines, which renders them as paragraphs.
+    pub loose: bool,
+}
+
+impl<'a> Parse<'a> for OrderedList<'a> {
+    /// Parse the input into an ordered list.
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        Self::parse_indented(input, 0)
+    }
+}
+
+impl<'a> OrderedList<'a> {
+    fn parse_indented(input: &'a str, min_indent: usize) -> IResult<&'a str, Self> {
+        Self::parse_indented_within(input, min_indent, Some)
+    }
+
+    /// Parses a list within a container like [`UnorderedList::parse_indented_within`].
+    fn parse_indented_within(
+        input: &'a str,
+        min_indent: usize,
+        strip: impl Fn(&'a str) -> Option<&'a str> + Copy,
+    ) -> IResult<&'a str, Self> {
+        fn number(input: &str) -> IResult<&str, &str> {
+            take_while_m_n(1, 9, |c: char| c.is_ascii_digit())(input)
+        }
+
+        let Some(line) = strip(input) else {
+            return fail(input);
+        };
+
+        let (_, (start, delimiter)) =
+            preceded(space0, pair(number, terminated(one_of(".)"), tag(" "))))(line)?;
+
+        let marker = move |i| recognize(tuple((number, char(delimiter), tag(" "))))(i);
+        let (rest, (items, loose)) = parse_list_items(input, min_indent, marker, strip)?;
+
+        Ok((
+            rest,
+            OrderedList {
+                items,
+                start: start.parse().unwrap_or_default(),
+                delimiter,
+                loose,
+            },
+        ))
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for OrderedList<'a> {
+    type Item = ListItem<'a>;
+    type IntoIter = <Vec<ListItem<'a>> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+impl<'a> Index<usize> for OrderedList<'a> {
+    type Output = str;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.items[index].text
+    }
+}
+
+/// A reference to a footnote.
+///
+/// # Example
+/// ```markdown
+/// [^note]
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct FootnoteRef<'a> {
+    pub name: &'a str,
+    /// The number of the footnote, set by [`footnote::resolve`] if the footnote is defined.
+    pub number: Option<usize>,
+    /// Counts the references to the same footnote from 1, so each can have its own backref.
+    pub occurrence: usize,
+}
+
+impl<'a> FootnoteRef<'a> {
+    pub fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        let (rest, _) = tag("[^")(input)?;
+        let (rest, name) = take_until("]")(rest)?;
+        let (rest, _) = tag("]")(rest)?;
+
+        Ok((
+            rest,
+            Self {
+                name,
+                number: None,
+                occurrence: 0,
+            },
+        ))
+    }
+
+    pub fn parse_into_text_block(input: &'a str) -> IResult<&'a str, TextBlockItem<'a>> {
+        let (rest, inner) = Self::parse(input)?;
+        Ok((rest, TextBlockItem::FootnoteRef(inner)))
+    }
+}
+
+/// Parses the body of a container such as a footnote: the blocks starting on the current line
+/// followed by blocks indented by four columns, with blank lines between them. Unindented lines
+/// continue a paragraph unless `ends` is true for them.
+fn parse_container<'a>(
+    input: &'a str,
+    options: &Options,
+    ends: fn(&str) -> bool,
+) -> IResult<&'a str, Vec<Block<'a>>> {
+    let mut len = 0;
+    let mut end = 0;
+    let mut previous_blank = false;
+
+    for (i, line) in input.split_inclusive('\n').enumerate() {
+        let blank = line.trim().is_empty();
+        let indented = strip_columns(line, 4).1 == 4;
+        let lazy = !previous_blank && !interrupts_paragraph(line, options) && !ends(line);
+
+        if i == 0 || (!blank && (indented || lazy)) {
+            end = len + line.len();
+        } else if !blank {
+            break;
+        }
+
+        len += line.len();
+        previous_blank = blank;
+    }
+
+    let (mut body, rest) = input.split_at(end);
+    let mut blocks = Vec::new();
+
+    loop {
+        let (next, _) = parse_blank_lines(body)?;
+
+        if next.is_empty() {
+            break;
+        }
+
+        let (next, block) = parse_container_block(next, options)?;
+        blocks.push(block);
+        body = next;
+    }
+
+    Ok((rest, blocks))
+}
+
+/// Parses a block of the body of a container. Lists and code are parsed along with the
+/// indentation of the body, other blocks like they are at the top level after it.
+fn parse_container_block<'a>(input: &'a str, options: &Options) -> IResult<&'a str, Block<'a>> {
+    let (content, indent) = strip_columns(input, 4);
+
+    UnorderedList::parse_indented(input, indent)
+        .map(|(rest, list)| (rest, Block::from(list)))
+        .or_else(|_| {
+            OrderedList::parse_indented(input, indent).map(|(rest, list)| (rest, list.into()))
+        })
+        .or_else(|_| {
+            alt((
+                |i| CodeBlock::parse_fenced_in(i, indent),
+                |i| CodeBlock::parse_indented_in(i, indent),
+            ))(input)
+            .map(|(rest, code)| (rest, code.into()))
+        })
+        .or_else(|_| Block::parse_block(content, options))
+}
+
+/// A footnote, its first line and the blocks indented by four spaces after it.
+///
+/// # Example
+/// ```markdown
+/// [^1]: My reference.
+///
+///     A second paragraph.
+///
+///     - and a list
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Footnote<'a> {
+    pub name: &'a str,
+    pub blocks: Vec<Block<'a>>,
+    /// The number of the footnote in the order of the first references to each footnote, set by
+    /// [`footnote::resolve`]. `None` if the footnote isn't referenced.
+    pub number: Option<usize>,
+    /// The number of references to the footnote, each of which has a backref.
+    pub references: usize,
+}
+
+impl<'a> Parse<'a> for Footnote<'a> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        Self::parse_with(input, &Options::default())
+    }
+}
+
+impl<'a> Footnote<'a> {
+    pub fn parse_with(input: &'a str, options: &Options) -> IResult<&'a str, Self> {
+        let (rest, name) = Self::parse_start(input)?;
+        let (rest, _) = space0(rest)?;
+        let (rest, blocks) =
+            parse_container(rest, options, |line| Self::parse_start(line).is_ok())?;
+
+        Ok((
+            rest,
+            Self {
+                name,
+                blocks,
+                number: None,
+                references: 0,
+            },
+        ))
+    }
+
+    /// Parses the `[^label]:` that starts a definition, a footnote only ends at the start of
+    /// another definition and not at a reference such as `[^label]` at the start of a line.
+    fn parse_start(input: &str) -> IResult<&str, &str> {
+        let (rest, _) = tag("[^")(input)?;
+        let (rest, name) =
+            verify(take_until("]:"), |name: &str| !name.contains(['\n', ']']))(rest)?;
+        let (rest, _) = tag("]:")(rest)?;
+
+        Ok((rest, name))
     }
 }
 
-impl<'a> From<TaskList<'a>> for Block<'a> {
-    fn from(task_list: TaskList<'a>) -> Self {
-        Block::TaskList(task_list)
+/// A list of terms and their definitions, see [`Options::definition_lists`].
+///
+/// # Example
+/// ```markdown
+/// Apple
+/// : A fruit.
+///
+///     Grows on trees.
+/// : A company.
+///
+/// Orange
+/// : A fruit and a colour.
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct DefinitionList<'a> {
+    pub items: Vec<DefinitionItem<'a>>,
+}
+
+/// One or more terms, each on its own line, followed by their definitions. A definition can
+/// continue with blocks indented by four spaces.
+#[derive(Debug, PartialEq)]
+pub struct DefinitionItem<'a> {
+    pub terms: Vec<Vec<TextBlockItem<'a>>>,
+    pub definitions: Vec<Vec<Block<'a>>>,
+}
+
+/// Whether the line starts a definition with a `:` marker.
+fn is_definition_marker(line: &str) -> bool {
+    tuple((parse_indentation, char(':'), space1))(line).is_ok()
+}
+
+impl<'a> DefinitionList<'a> {
+    pub fn parse_with(input: &'a str, options: &Options) -> IResult<&'a str, Self> {
+        if !options.definition_lists {
+            return fail(input);
+        }
+
+        let (mut rest, item) = Self::parse_item(input, options)?;
+        let mut items = vec![item];
+
+        // blank lines between items are part of the list if another item follows them
+        loop {
+            let (next, _) = parse_blank_lines(rest)?;
+
+            let Ok((next, item)) = Self::parse_item(next, options) else {
+                break;
+            };
+
+            items.push(item);
+            rest = next;
+        }
+
+        Ok((rest, Self { items }))
+    }
+
+    fn parse_item(input: &'a str, options: &Options) -> IResult<&'a str, DefinitionItem<'a>> {
+        let term = |input: &'a str| -> IResult<&'a str, Vec<TextBlockItem<'a>>> {
+            let (rest, line) = verify(parse_line, |line: &str| {
+                !line.trim().is_empty()
+                    && !is_definition_marker(line)
+                    && !interrupts_paragraph(line, options)
+            })(input)?;
+
+            let (_, items) = all_consuming(|i| parse_inline(i, options))(line.trim())?;
+
+            Ok((rest, items))
+        };
+
+        let definition = |input: &'a str| -> IResult<&'a str, Vec<Block<'a>>> {
+            let (rest, _) =
+                tuple((parse_blank_lines, parse_indentation, char(':'), space1))(input)?;
+            parse_container(rest, options, is_definition_marker)
+        };
+
+        let (rest, (terms, definitions)) = pair(many1(term), many1(definition))(input)?;
+
+        Ok((rest, DefinitionItem { terms, definitions }))
+    }
+}
+
+/// A callout such as a note or a warning, see [`Options::admonitions`]. Written as a container
+/// directive, closed by a line of at least as many colons as opened it, or as a GitHub alert.
+///
+/// # Example
+/// ```markdown
+/// :::tip[Did you know?]
+/// Admonitions can contain **any** blocks.
+/// :::
+///
+/// > [!WARNING]
+/// > Alerts are blockquotes.
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Admonition<'a> {
+    /// The kind as written, such as `tip` or, for an alert, `WARNING`.
+    pub kind: &'a str,
+    /// The title in brackets after the kind of a directive, always `None` for an alert since
+    /// GitHub alerts can't have one.
+    pub title: Option<&'a str>,
+    pub blocks: Vec<Block<'a>>,
+}
+
+/// The kinds of GitHub alerts, which are matched ignoring case.
+const ALERT_KINDS: [&str; 5] = ["NOTE", "TIP", "IMPORTANT", "WARNING", "CAUTION"];
+
+impl<'a> Admonition<'a> {
+    pub fn parse_with(input: &'a str, options: &Options) -> IResult<&'a str, Self> {
+        if !options.admonitions {
+            return fail(input);
+        }
+
+        alt((
+            |i| Self::parse_directive(i, options),
+            |i| Self::parse_alert(i, options),
+        ))(input)
+    }
+
+    /// Parses a container directive, opened by `:::kind Title` or `:::kind[Title]`. Without a
+    /// closing line the directive runs to the end of the input.
+    fn parse_directive(input: &'a str, options: &Options) -> IResult<&'a str, Self> {
+        let (rest, _) = parse_indentation(input)?;
+        let (rest, fence) =
+            verify(take_while1(|c| c == ':'), |fence: &str| fence.len() >= 3)(rest)?;
+        let (rest, kind) =
+            take_while1(|c: char| c.is_alphanumeric() || c == '-' || c == '_')(rest)?;
+        let (start, title) = parse_last_line(rest)?;
+
+        let title = title.trim();
+        let title = title
+            .strip_prefix('[')
+            .and_then(|label| label.strip_suffix(']'))
+            .unwrap_or(title)
+            .trim();
+        let title = (!title.is_empty()).then_some(title);
+
+        let mut rest = start;
+        let mut end = start.len();
+        // the fence of the code block the line is in, whose lines can't close the directive
+        let mut code = None;
+
+        while !rest.is_empty() {
+            let (next, line) = parse_last_line(rest)?;
+
+            match code {
+                Some(fence) if is_closing_fence(line, fence) => code = None,
+                Some(_) => {}
+                None if is_closing_fence(line, fence) => {
+                    end = start.len() - rest.len();
+                    rest = next;
+                    break;
+                }
+                None => {
+                    code = preceded(parse_indentation, parse_fence)(line)
+                        .ok()
+                        .map(|f| f.1)
+                }
+            }
+
+            rest = next;
+        }
+
+        let (_, blocks) = Block::parse_blocks(&start[..end], options)?;
+
+        Ok((
+            rest,
+            Self {
+                kind,
+                title,
+                blocks,
+            },
+        ))
+    }
+
+    /// Parses a GitHub alert, a blockquote starting with a `[!KIND]` line. Without their `>`
+    /// markers the lines are parsed like blocks at the top level, except that only paragraphs,
+    /// lists and fenced code can span lines.
+    fn parse_alert(input: &'a str, options: &Options) -> IResult<&'a str, Self> {
+        let (mut rest, kind) = delimited(
+            tuple((parse_indentation, char('>'), space0, tag("[!"))),
+            take_while1(|c: char| c.is_ascii_alphabetic()),
+            tuple((char(']'), space0, alt((line_ending, eof)))),
+        )(input)?;
+
+        if !ALERT_KINDS.iter().any(|k| k.eq_ignore_ascii_case(kind)) {
+            return fail(input);
+        }
+
+        let mut blocks: Vec<Block<'a>> = Vec::new();
+        // whether the previous line is paragraph text, and whether it ends with a hard break
+        let mut paragraph = None;
+
+        while !rest.is_empty() {
+            let (next, _) = parse_last_line(rest)?;
+
+            // the line along with its line ending, which some blocks expect
+            let Some(line) = strip_quote_marker(&rest[..rest.len() - next.len()]) else {
+                break;
+            };
+
+            if line.trim().is_empty() {
+                paragraph = None;
+                rest = next;
+                continue;
+            }
+
+            let continues = paragraph.is_some() && !interrupts_paragraph(line, options);
+
+            if !continues {
+                // the blocks that span lines are parsed with the markers stripped from each line
+                let spanning = alt((
+                    map(
+                        |i| CodeBlock::parse_fenced_within(i, strip_quote_marker),
+                        Block::from,
+                    ),
+                    map(
+                        |i| UnorderedList::parse_indented_within(i, 0, strip_quote_marker),
+                        Block::from,
+                    ),
+                    map(
+                        |i| OrderedList::parse_indented_within(i, 0, strip_quote_marker),
+                        Block::from,
+                    ),
+                ))(rest);
+
+                if let Ok((next, block)) = spanning {
+                    blocks.push(block);
+                    paragraph = None;
+                    rest = next;
+                    continue;
+                }
+
+                match Block::parse_block(line, options) {
+                    Ok((_, Block::TextBlock(_))) | Err(_) => {}
+                    Ok((_, block)) => {
+                        blocks.push(block);
+                        paragraph = None;
+                        rest = next;
+                        continue;
+                    }
+                }
+            }
+
+            let text = line.trim();
+            let (text, hard) = match text.strip_suffix('\\') {
+                Some(text) if !text.is_empty() => (text, true),
+                _ => (text, line.trim_end_matches(['\r', '\n']).ends_with("  ")),
+            };
+
+            let (_, items) = all_consuming(|i| parse_inline(i, options))(text)?;
+
+            match (blocks.last_mut(), paragraph) {
+                (Some(Block::TextBlock(text_block)), Some(previous_hard)) if continues => {
+                    text_block.contents.push(match previous_hard {
+                        true => TextBlockItem::HardBreak,
+                        false => TextBlockItem::SoftBreak,
+                    });
+                    text_block.contents.extend(items);
+                }
+                _ => blocks.push(Block::TextBlock(TextBlock { contents: items })),
+            }
+
+            paragraph = Some(hard);
+            rest = next;
+        }
+
+        Ok((
+            rest,
+            Self {
+                kind,
+                title: None,
+                blocks,
+            },
+        ))
+    }
+}
+
+/// Removes the `>` marker of a blockquote from a line, returning `None` for a line without one.
+fn strip_quote_marker(line: &str) -> Option<&str> {
+    let (rest, _) = pair(parse_indentation, char::<_, Error<&str>>('>'))(line).ok()?;
+    Some(rest.strip_prefix(' ').unwrap_or(rest))
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Text<'a>(pub &'a str);
+
+impl<'a> Text<'a> {
+    pub fn parse_into_text_block(input: &'a str) -> IResult<&'a str, TextBlockItem<'a>> {
+        let (rest, text) = Self::parse(input)?;
+        Ok((rest, TextBlockItem::Text(text)))
+    }
+
+    /// Returns the text with backslash escapes and entity references decoded, the source is
+    /// available as `.0`.
+    pub fn value(&self) -> Cow<'a, str> {
+        unescape(self.0)
+    }
+
+    /// Parses text up to the start of an inline element or a delimiter run. The first character
+    /// is always text, as it doesn't start either.
+    fn parse_until(input: &'a str, options: &Options) -> IResult<&'a str, Self> {
+        let mut previous = None;
+        let mut escaped = false;
+
+        let end = input
+            .char_indices()
+            .find(|&(i, c)| {
+                let before = previous.replace(c);
+
+                // an escaped punctuation character is never the start of an element
+                let literal = escaped && c.is_ascii_punctuation();
+                escaped = c == '\\' && !escaped;
+
+                if i == 0 || literal {
+                    return false;
+                }
+
+                let word_start =
+                    before.is_none_or(|p| p.is_whitespace() || matches!(p, '*' | '_' | '~' | '('));
+
+                match c {
+                    _ if is_delimiter(c, options) => true,
+                    '`' | '[' | '!' | '<' | '{' | '$' => {
+                        TextBlockItem::parse_element(&input[i..], options).is_ok()
+                    }
+                    ' ' | '\t' | '\\' | '\r' | '\n' => {
+                        TextBlockItem::parse_break(&input[i..]).is_ok()
+                    }
+                    _ => word_start && autolink::parse_extended_autolink(&input[i..]).is_ok(),
+                }
+            })
+            .map_or(input.len(), |(i, _)| i);
+
+        match end {
+            0 => fail(input),
+            _ => Ok((&input[end..], Self(&input[..end]))),
+        }
+    }
+}
+
+impl<'a> Parse<'a> for Text<'a> {
+    /// Parses text up to the start of an inline element, a `!` only starts an image before `[`
+    /// and a bare URL or email address is only linked at the start of a word.
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        Self::parse_until(input, &Options::default())
+    }
+}
+
+/// A code span, text within a run of backticks closed by a run of the same length.
+///
+/// # Example
+/// ```markdown
+/// `let x = 1;` or `` a ` backtick ``
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct InlineCode<'a>(pub &'a str);
+
+impl<'a> Parse<'a> for InlineCode<'a> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        let (rest, fence) = take_while1(|c| c == '`')(input)?;
+
+        let mut offset = 0;
+
+        while let Some(start) = rest[offset..].find('`') {
+            let start = offset + start;
+            let run = rest[start..].len() - rest[start..].trim_start_matches('`').len();
+
+            if run == fence.len() {
+                let code = &rest[..start];
+
+                // a single space on both sides is removed, so code can start or end with a backtick
+                let code = match code.strip_prefix(' ').and_then(|c| c.strip_suffix(' ')) {
+                    Some(inner) if !inner.trim().is_empty() => inner,
+                    _ => code,
+                };
+
+                return Ok((&rest[start + run..], Self(code)));
+            }
+
+            offset = start + run;
+        }
+
+        fail(input)
+    }
+}
+
+/// LaTeX math, see [`Options::math`]. Math between `$$` is displayed, in a block or inline, while
+/// math between single `$` is inline. The contents are kept as they are, so braces within them
+/// aren't MDX expressions.
+///
+/// # Example
+/// ```markdown
+/// The area is $\pi r^2$, not $5 or $10.
+///
+/// $$
+/// \int_0^1 f(x) \, dx
+/// $$
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Math<'a> {
+    pub contents: &'a str,
+    pub display: bool,
+}
+
+impl<'a> Math<'a> {
+    /// Parses inline math. A single `$` must be followed by a non-whitespace character, and the
+    /// closing `$` preceded by one and not followed by a digit, so amounts such as `$5 and $10`
+    /// aren't math.
+    pub fn parse_inline(input: &'a str, options: &Options) -> IResult<&'a str, Self> {
+        if !options.math {
+            return fail(input);
+        }
+
+        if let Some(rest) = input.strip_prefix("$$") {
+            return match rest.find("$$") {
+                Some(end) if !rest[..end].trim().is_empty() => Ok((
+                    &rest[end + 2..],
+                    Self {
+                        contents: rest[..end].trim(),
+                        display: true,
+                    },
+                )),
+                _ => fail(input),
+            };
+        }
+
+        let Some(rest) = input.strip_prefix('$') else {
+            return fail(input);
+        };
+
+        if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+            return fail(input);
+        }
+
+        for (i, _) in rest.match_indices('$').filter(|&(i, _)| i > 0) {
+            let (contents, after) = (&rest[..i], &rest[i + 1..]);
+            let backslashes = contents.len() - contents.trim_end_matches('\\').len();
+
+            if !contents.ends_with(char::is_whitespace)
+                && backslashes.is_multiple_of(2)
+                && !after.starts_with(|c: char| c.is_ascii_digit())
+            {
+                let math = Self {
+                    contents,
+                    display: false,
+                };
+
+                return Ok((after, math));
+            }
+        }
+
+        fail(input)
+    }
+
+    /// Parses a block of display math, either a line of `$$` followed by the lines up to one
+    /// ending with `$$`, or a single line starting and ending with `$$`. The block can't contain
+    /// blank lines, without a closing `$$` before one the lines are a paragraph.
+    pub fn parse_block(input: &'a str, options: &Options) -> IResult<&'a str, Self> {
+        if !options.math {
+            return fail(input);
+        }
+
+        let (start, _) = pair(parse_indentation, tag("$$"))(input)?;
+        let (mut rest, first) = parse_last_line(start)?;
+
+        let math = |contents: &'a str| Self {
+            contents,
+            display: true,
+        };
+
+        if let Some(contents) = first.trim_end().strip_suffix("$$") {
+            return match contents.trim() {
+                "" => fail(input),
+                contents => Ok((rest, math(contents))),
+            };
+        }
+
+        if !first.trim().is_empty() {
+            return fail(input);
+        }
+
+        let body = rest;
+
+        while !rest.is_empty() {
+            let (next, line) = parse_last_line(rest)?;
+
+            if line.trim().is_empty() {
+                break;
+            }
+
+            if let Some(contents) = line.trim_end().strip_suffix("$$") {
+                let offset = body.len() - rest.len();
+
+                return match body[..offset + contents.len()].trim() {
+                    "" => fail(input),
+                    contents => Ok((next, math(contents))),
+                };
+            }
+
+            rest = next;
+        }
+
+        fail(input)
+    }
+}
+
+/// Raw HTML, an HTML block or inline tags and comments.
+///
+/// # Example
+/// ```markdown
+/// <details><summary>More</summary> <!-- a comment -->
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Html<'a>(pub &'a str);
+
+/// JSX in MDX, a block of tags or an inline tag.
+///
+/// # Example
+/// ```markdown
+/// <Callout type="info">Some <Badge /> text</Callout>
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Jsx<'a>(pub &'a str);
+
+/// A JavaScript expression in MDX, without the braces around it.
+///
+/// # Example
+/// ```markdown
+/// {props.title} {/* a comment */}
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct Expression<'a>(pub &'a str);
+
+#[derive(Debug, PartialEq)]
+pub enum TextBlockItem<'a> {
+    Text(Text<'a>),
+    Code(InlineCode<'a>),
+    FootnoteRef(FootnoteRef<'a>),
+    Link(Link<'a>),
+    Image(Image<'a>),
+    /// `*emphasis*` or `_emphasis_`
+    Emphasis(Vec<TextBlockItem<'a>>),
+    /// `**strong**` or `__strong__`
+    Strong(Vec<TextBlockItem<'a>>),
+    /// `~~strikethrough~~`, see [`Options::strikethrough`].
+    Strikethrough(Vec<TextBlockItem<'a>>),
+    /// `==highlight==`, see [`Options::highlight`].
+    Highlight(Vec<TextBlockItem<'a>>),
+    /// `^superscript^`, see [`Options::superscript`].
+    Superscript(Vec<TextBlockItem<'a>>),
+    /// `~subscript~`, see [`Options::subscript`].
+    Subscript(Vec<TextBlockItem<'a>>),
+    /// A line ending within a paragraph.
+    SoftBreak,
+    /// A line ending after two or more spaces or a backslash.
+    HardBreak,
+    /// Inline raw HTML, see [`Mode::Markdown`].
+    Html(Html<'a>),
+    /// An inline JSX tag, see [`Mode::Mdx`].
+    Jsx(Jsx<'a>),
+    /// An inline expression, see [`Mode::Mdx`].
+    Expression(Expression<'a>),
+    /// `$inline$` or `$$display$$` math, see [`Options::math`].
+    Math(Math<'a>),
+}
+
+impl<'a> TextBlockItem<'a> {
+    /// Parses a line ending along with the whitespace around it.
+    fn parse_break(input: &'a str) -> IResult<&'a str, Self> {
+        let (rest, marker) = alt((tag("\\"), space0))(input)?;
+        let (rest, _) = pair(line_ending, space0)(rest)?;
+
+        match marker == "\\" || marker.len() >= 2 {
+            true => Ok((rest, TextBlockItem::HardBreak)),
+            false => Ok((rest, TextBlockItem::SoftBreak)),
+        }
+    }
+
+    /// Parses an inline element other than text and the spans between delimiter runs.
+    fn parse_element(input: &'a str, options: &Options) -> IResult<&'a str, Self> {
+        alt((
+            Self::parse_break,
+            map(InlineCode::parse, TextBlockItem::Code),
+            FootnoteRef::parse_into_text_block,
+            Image::parse_into_text_block,
+            Link::parse_into_text_block,
+            map(|i| Math::parse_inline(i, options), TextBlockItem::Math),
+            |i| Self::parse_raw(i, options),
+            map(Link::parse_extended_autolink, TextBlockItem::Link),
+        ))(input)
+    }
+
+    /// Parses an autolink or inline HTML, or in MDX a JSX tag or an expression.
+    fn parse_raw(input: &'a str, options: &Options) -> IResult<&'a str, Self> {
+        match options.mode {
+            Mode::Markdown => alt((
+                map(Link::parse_autolink, TextBlockItem::Link),
+                map(html::parse_inline, |html| TextBlockItem::Html(Html(html))),
+            ))(input),
+            Mode::Mdx => alt((
+                map(jsx::parse_tag, |jsx| TextBlockItem::Jsx(Jsx(jsx))),
+                map(jsx::parse_expression, |expression| {
+                    TextBlockItem::Expression(Expression(expression))
+                }),
+            ))(input),
+        }
+    }
+
+    /// Returns the span for a delimiter used `count` times on both sides, `~~` is only
+    /// strikethrough and `~` only a subscript.
+    fn span(delimiter: char, count: usize, options: &Options) -> Option<Span<'a>> {
+        match (delimiter, count) {
+            ('*' | '_', 1) => Some(TextBlockItem::Emphasis),
+            ('*' | '_', 2) => Some(TextBlockItem::Strong),
+            ('~', 2) if options.strikethrough => Some(TextBlockItem::Strikethrough),
+            ('=', 2) if options.highlight => Some(TextBlockItem::Highlight),
+            ('^', 1) if options.superscript => Some(TextBlockItem::Superscript),
+            ('~', 1) if options.subscript => Some(TextBlockItem::Subscript),
+            _ => None,
+        }
+    }
+}
+
+type Span<'a> = fn(Vec<TextBlockItem<'a>>) -> TextBlockItem<'a>;
+
+/// Whether a character starts a delimiter run with the given options.
+fn is_delimiter(c: char, options: &Options) -> bool {
+    match c {
+        '*' | '_' => true,
+        '~' => options.strikethrough || options.subscript,
+        '=' => options.highlight,
+        '^' => options.superscript,
+        _ => false,
+    }
+}
+
+/// A run of the same delimiter character, such as `**`, which can open or close spans depending
+/// on the characters around it.
+struct DelimiterRun {
+    delimiter: char,
+    /// The part of the run that hasn't been used by a span.
+    range: Range<usize>,
+    /// The length of the whole run.
+    length: usize,
+    opens: bool,
+    closes: bool,
+}
+
+impl DelimiterRun {
+    /// Parses the run at `start`, whether it's left or right flanking decides if it can open or
+    /// close a span. For `_` a run within a word can do neither.
+    fn parse(input: &str, start: usize, options: &Options) -> Option<Self> {
+        let delimiter = input[start..].chars().next()?;
+
+        if !is_delimiter(delimiter, options) {
+            return None;
+        }
+
+        let end = input.len() - input[start..].trim_start_matches(delimiter).len();
+
+        let before = input[..start].chars().next_back();
+        let after = input[end..].chars().next();
+        let whitespace = |c: Option<char>| c.is_none_or(char::is_whitespace);
+        let punctuation = |c: Option<char>| c.is_some_and(|c| c.is_ascii_punctuation());
+
+        let left = !whitespace(after)
+            && (!punctuation(after) || whitespace(before) || punctuation(before));
+        let right = !whitespace(before)
+            && (!punctuation(before) || whitespace(after) || punctuation(after));
+
+        let (opens, closes) = match delimiter {
+            '_' => (
+                left && (!right || punctuation(before)),
+                right && (!left || punctuation(after)),
+            ),
+            _ => (left, right),
+        };
+
+        Some(Self {
+            delimiter,
+            range: start..end,
+            length: end - start,
+            opens,
+            closes,
+        })
+    }
+
+    /// Returns how many delimiters a span between this opener and a closer uses and the span,
+    /// if they can form one. Superscripts and subscripts can't contain whitespace.
+    fn matches<'a>(
+        &self,
+        closer: &Self,
+        input: &str,
+        options: &Options,
+    ) -> Option<(usize, Span<'a>)> {
+        if self.delimiter != closer.delimiter {
+            return None;
+        }
+
+        let (opener, closer_length) = (self.range.len(), closer.range.len());
+
+        let count = match self.delimiter {
+            '*' | '_' => {
+                // a run that can both open and close only pairs with a run whose length adds up
+                // to a multiple of three when both are
+                let both = (self.opens && self.closes) || (closer.opens && closer.closes);
+                let sum = self.length + closer.length;
+
+                if both
+                    && sum.is_multiple_of(3)
+                    && !(self.length.is_multiple_of(3) && closer.length.is_multiple_of(3))
+                {
+                    return None;
+                }
+
+                match opener >= 2 && closer_length >= 2 {
+                    true => 2,
+                    false => 1,
+                }
+            }
+            _ if opener == closer_length => opener,
+            _ => return None,
+        };
+
+        let contents = &input[self.range.end..closer.range.start];
+
+        if count == 1
+            && matches!(self.delimiter, '^' | '~')
+            && contents.contains(char::is_whitespace)
+        {
+            return None;
+        }
+
+        TextBlockItem::span(self.delimiter, count, options).map(|span| (count, span))
+    }
+}
+
+/// Inline content while spans are being matched.
+enum Inline<'a> {
+    Item(TextBlockItem<'a>),
+    Text(Range<usize>),
+    Run(DelimiterRun),
+}
+
+/// Turns inline content into items, delimiter runs that aren't part of a span become text and
+/// adjacent text is joined.
+fn finish_inline<'a>(
+    input: &'a str,
+    inlines: impl IntoIterator<Item = Inline<'a>>,
+) -> Vec<TextBlockItem<'a>> {
+    let mut items = Vec::new();
+    let mut text: Option<Range<usize>> = None;
+
+    for inline in inlines {
+        let range = match inline {
+            Inline::Item(item) => {
+                if let Some(range) = text.take() {
+                    items.push(TextBlockItem::Text(Text(&input[range])));
+                }
+
+                items.push(item);
+                continue;
+            }
+            Inline::Text(range) | Inline::Run(DelimiterRun { range, .. }) => range,
+        };
+
+        text = match text {
+            Some(previous) if previous.end == range.start => Some(previous.start..range.end),
+            Some(previous) => {
+                items.push(TextBlockItem::Text(Text(&input[previous])));
+                Some(range)
+            }
+            None => Some(range),
+        };
+    }
+
+    if let Some(range) = text {
+        items.push(TextBlockItem::Text(Text(&input[range])));
+    }
+
+    items
+}
+
+/// Parses all of the input as inline content. Emphasis and the other spans are matched with a
+/// stack of the delimiter runs that can open one, so unmatched delimiters don't cause the text
+/// after them to be parsed again.
+///
+/// # Example
+/// ```
+/// use mdx_rs::parser::markdown::{parse_inline, Options, Text, TextBlockItem};
+///
+/// let (_, items) = parse_inline("**a*", &Options::default()).unwrap();
+///
+/// assert_eq!(
+///     items,
+///     vec![
+///         TextBlockItem::Text(Text("*")),
+///         TextBlockItem::Emphasis(vec![TextBlockItem::Text(Text("a"))]),
+///     ]
+/// );
+/// ```
+pub fn parse_inline<'a>(
+    input: &'a str,
+    options: &Options,
+) -> IResult<&'a str, Vec<TextBlockItem<'a>>> {
+    let mut inlines: Vec<Inline<'a>> = Vec::new();
+    // the indices of the runs in `inlines` that can open a span
+    let mut openers: Vec<usize> = Vec::new();
+    // the number of openers known not to match a closer of a given delimiter, length modulo
+    // three and whether it can open, so they aren't searched again
+    let mut bottoms: HashMap<(char, usize, bool), usize> = HashMap::new();
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        let start = input.len() - rest.len();
+
+        let Some(mut run) = DelimiterRun::parse(input, start, options) else {
+            let (next, inline) = match TextBlockItem::parse_element(rest, options) {
+                Ok((next, item)) => (next, Inline::Item(item)),
+                Err(_) => {
+                    let (next, _) = Text::parse_until(rest, options)?;
+                    (next, Inline::Text(start..input.len() - next.len()))
+                }
+            };
+
+            inlines.push(inline);
+            rest = next;
+            continue;
+        };
+
+        rest = &input[run.range.end..];
+
+        while run.closes && !run.range.is_empty() {
+            let length = match run.delimiter {
+                '*' | '_' => run.length % 3,
+                _ => run.length,
+            };
+            let key = (run.delimiter, length, run.opens);
+            let bottom = bottoms.get(&key).copied().unwrap_or(0);
+
+            let found =
+                openers[bottom..]
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .find_map(|(position, &index)| match &inlines[index] {
+                        Inline::Run(opener) => opener
+                            .matches(&run, input, options)
+                            .map(|(count, span)| (bottom + position, count, span)),
+                        _ => None,
+                    });
+
+            let Some((position, count, span)) = found else {
+                bottoms.insert(key, openers.len());
+                break;
+            };
+
+            // the runs between the opener and the closer can no longer open a span
+            let index = openers[position];
+            openers.truncate(position + 1);
+            run.range.start += count;
+
+            let contents = finish_inline(input, inlines.drain(index + 1..));
+
+            if let Some(Inline::Run(opener)) = inlines.last_mut() {
+                opener.range.end -= count;
+
+                if opener.range.is_empty() {
+                    openers.pop();
+                    inlines.pop();
+                }
+            }
+
+            inlines.push(Inline::Item(span(contents)));
+
+            for bottom in bottoms.values_mut() {
+                *bottom = (*bottom).min(openers.len());
+            }
+        }
+
+        if !run.range.is_empty() {
+            if run.opens {
+                openers.push(inlines.len());
+            }
+
+            inlines.push(Inline::Run(run));
+        }
+    }
+
+    Ok((rest, finish_inline(input, inlines)))
+}
+
+/// Decodes backslash escapes of ASCII punctuation and entity references such as `&copy;`.
+pub fn unescape(text: &str) -> Cow<'_, str> {
+    if !text.contains(['\\', '&']) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut value = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let escaped = rest
+            .strip_prefix('\\')
+            .and_then(|r| r.chars().next())
+            .filter(char::is_ascii_punctuation);
+
+        if let Some(escaped) = escaped {
+            value.push(escaped);
+            rest = &rest[2..];
+        } else if let Some((decoded, len)) = entity::decode(rest) {
+            value.push_str(&decoded);
+            rest = &rest[len..];
+        } else {
+            value.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    Cow::Owned(value)
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TextBlock<'a> {
+    pub contents: Vec<TextBlockItem<'a>>,
+}
+
+impl<'a> TextBlock<'a> {
+    pub fn len(&self) -> usize {
+        self.contents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.contents.is_empty()
+    }
+
+    pub fn parse_with(input: &'a str, options: &Options) -> IResult<&'a str, Self> {
+        // the paragraph ends at a blank line or a line that starts another block
+        let mut end = 0;
+
+        for line in input.split_inclusive('\n') {
+            if line.trim().is_empty() || (end > 0 && interrupts_paragraph(line, options)) {
+                break;
+            }
+
+            end += line.len();
+        }
+
+        if end == 0 {
+            return fail(input);
+        }
+
+        let (contents, rest) = input.split_at(end);
+        let contents = contents.trim_end();
+        let (rest, _) = many0(line_ending)(rest)?;
+
+        let (_, contents) = all_consuming(|i| parse_inline(i, options))(contents)?;
+
+        Ok((rest, Self { contents }))
+    }
+}
+
+impl<'a> Parse<'a> for TextBlock<'a> {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        Self::parse_with(input, &Options::default())
+    }
+}
+
+/// Flattens inline markdown into plain text, keeping the text of links and the alt text of
+/// images and dropping footnote references.
+pub fn plain_text(input: &str) -> String {
+    plain_text_with(input, &Options::default())
+}
+
+/// Flattens inline markdown into plain text like [`plain_text`], parsing the extension spans
+/// enabled in `options` so their markers are dropped too.
+pub fn plain_text_with(input: &str, options: &Options) -> String {
+    fn push<'a>(text: &mut String, items: &[TextBlockItem<'a>], options: &Options) {
+        for item in items {
+            match item {
+                TextBlockItem::Text(inner) => text.push_str(&inner.value()),
+                TextBlockItem::Code(InlineCode(code)) => text.push_str(code),
+                TextBlockItem::Math(math) => text.push_str(math.contents),
+                TextBlockItem::Link(link) => text.push_str(&link.text_value_with(options)),
+                TextBlockItem::Image(image) => text.push_str(&image.alt_value_with(options)),
+                TextBlockItem::FootnoteRef(_)
+                | TextBlockItem::Html(_)
+                | TextBlockItem::Jsx(_)
+                | TextBlockItem::Expression(_) => {}
+                TextBlockItem::SoftBreak => text.push(' '),
+                TextBlockItem::HardBreak => text.push('\n'),
+                TextBlockItem::Emphasis(items)
+                | TextBlockItem::Strong(items)
+                | TextBlockItem::Strikethrough(items)
+                | TextBlockItem::Highlight(items)
+                | TextBlockItem::Superscript(items)
+                | TextBlockItem::Subscript(items) => push(text, items, options),
+            }
+        }
+    }
+
+    let mut text = String::new();
+
+    if let Ok((_, items)) = parse_inline(input, options) {
+        push(&mut text, &items, options);
+    }
+
+    text
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Newline;
+
+impl<'a> Parse<'a> for Newline {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        let (rest, _) = line_ending(input)?;
+        Ok((rest, Self))
+    }
+}
+
+/// A horizontal rule, three or more `-`, `*` or `_` characters optionally separated by spaces.
+///
+/// # Example
+/// ```markdown
+/// ---
+/// * * *
+/// ```
+#[derive(Debug, PartialEq)]
+pub struct ThematicBreak;
+
+impl<'a> Parse<'a> for ThematicBreak {
+    fn parse(input: &'a str) -> IResult<&'a str, Self> {
+        let (rest, _) = parse_indentation(input)?;
+        let (rest, line) = parse_last_line(rest)?;
+
+        let mut chars = line.chars().filter(|c| *c != ' ' && *c != '\t');
+        let marker = chars.next();
+        let count = chars.clone().count() + 1;
+
+        let valid = !line.starts_with([' ', '\t'])
+            && matches!(marker, Some('-' | '*' | '_'))
+            && count >= 3
+            && chars.all(|c| Some(c) == marker);
+
+        match valid {
+            true => Ok((rest, Self)),
+            false => fail(input),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Block<'a> {
+    Heading(Heading<'a>),
+    CodeBlock(CodeBlock<'a>),
+    Link(Link<'a>),
+    Image(Image<'a>),
+    OrderedList(OrderedList<'a>),
+    UnorderedList(UnorderedList<'a>),
+    Footnote(Footnote<'a>),
+    Definition(Definition<'a>),
+    DefinitionList(DefinitionList<'a>),
+    Admonition(Admonition<'a>),
+    TextBlock(TextBlock<'a>),
+    Html(Html<'a>),
+    Jsx(Jsx<'a>),
+    Expression(Expression<'a>),
+    Math(Math<'a>),
+    ThematicBreak(ThematicBreak),
+    Newline(Newline),
+}
+
+impl<'a> From<Heading<'a>> for Block<'a> {
+    fn from(heading: Heading<'a>) -> Self {
+        Block::Heading(heading)
+    }
+}
+
+impl<'a> From<CodeBlock<'a>> for Block<'a> {
+    fn from(code_block: CodeBlock<'a>) -> Self {
+        Block::CodeBlock(code_block)
+    }
+}
+
+impl<'a> From<Link<'a>> for Block<'a> {
+    fn from(link: Link<'a>) -> Self {
+        Block::Link(link)
+    }
+}
+
+impl<'a> From<Image<'a>> for Block<'a> {
+    fn from(image: Image<'a>) -> Self {
+        Block::Image(image)
+    }
+}
+
+impl<'a> From<OrderedList<'a>> for Block<'a> {
+    fn from(ordered_list: OrderedList<'a>) -> Self {
+        Block::OrderedList(ordered_list)
+    }
+}
+
+impl<'a> From<UnorderedList<'a>> for Block<'a> {
+    fn from(unordered_list: UnorderedList<'a>) -> Self {
+        Block::UnorderedList(unordered_list)
+    }
+}
+
+impl<'a> From<Footnote<'a>> for Block<'a> {
+    fn from(footnote: Footnote<'a>) -> Self {
+        Block::Footnote(footnote)
+    }
+}
+
+impl<'a> From<Definition<'a>> for Block<'a> {
+    fn from(definition: Definition<'a>) -> Self {
+        Block::Definition(definition)
+    }
+}
+
+impl<'a> From<DefinitionList<'a>> for Block<'a> {
+    fn from(definition_list: DefinitionList<'a>) -> Self {
+        Block::DefinitionList(definition_list)
+    }
+}
+
+impl<'a> From<Admonition<'a>> for Block<'a> {
+    fn from(admonition: Admonition<'a>) -> Self {
+        Block::Admonition(admonition)
+    }
+}
+
+impl<'a> From<TextBlock<'a>> for Block<'a> {
+    /// Converts a text block into a block, a text block with only a link or an image is a link or
+    /// image block.
+    fn from(mut text_block: TextBlock<'a>) -> Self {
+        match text_block.contents.pop() {
+            Some(TextBlockItem::Link(link)) if text_block.is_empty() => Block::Link(link),
+            Some(TextBlockItem::Image(image)) if text_block.is_empty() => Block::Image(image),
+            item => {
+                text_block.contents.extend(item);
+                Block::TextBlock(text_block)
+            }
+        }
+    }
+}
+
+impl<'a> From<Html<'a>> for Block<'a> {
+    fn from(html: Html<'a>) -> Self {
+        Block::Html(html)
+    }
+}
+
+impl<'a> From<Jsx<'a>> for Block<'a> {
+    fn from(jsx: Jsx<'a>) -> Self {
+        Block::Jsx(jsx)
+    }
+}
+
+impl<'a> From<Expression<'a>> for Block<'a> {
+    fn from(expression: Expression<'a>) -> Self {
+        Block::Expression(expression)
+    }
+}
+
+impl<'a> From<Math<'a>> for Block<'a> {
+    fn from(math: Math<'a>) -> Self {
+        Block::Math(math)
+    }
+}
+
+impl<'a> From<ThematicBreak> for Block<'a> {
+    fn from(thematic_break: ThematicBreak) -> Self {
+        Block::ThematicBreak(thematic_break)
+    }
+}
+
+impl<'a> From<Newline> for Block<'a> {
+    fn from(nl: Newline) -> Self {
+        Block::Newline(nl)
+    }
+}
+
+pub trait ParseIntoBlock<'a>: Parse<'a> {
+    fn parse_into_block(input: &'a str) -> IResult<&'a str, Block<'a>>;
+}
+
+impl<'a, T> ParseIntoBlock<'a> for T
+where
+    T: Parse<'a> + Into<Block<'a>>,
+{
+    fn parse_into_block(input: &'a str) -> IResult<&'a str, Block<'a>> {
+        let (rest, out) = Self::parse(input)?;
+        let out = out.into();
+        Ok((rest, out))
+    }
+}
+
+impl<'a> Block<'a> {
+    /// Makes the generated ids of the headings unique across the blocks and the containers
+    /// within them, custom ids are kept as they are and are reserved before any slug is generated.
+    fn assign_heading_ids(blocks: &mut [Self]) {
+        let mut headings = Vec::new();
+        Self::headings_mut(blocks, &mut headings);
+
+        let mut slugger = Slugger::new();
+
+        for heading in &headings {
+            if let HeadingId::Custom(id) = heading.id {
+                slugger.reserve(id);
+            }
+        }
+
+        for heading in headings {
+            if let HeadingId::Slug(slug) = &mut heading.id {
+                *slug = slugger.unique(slug);
+            }
+        }
+    }
+
+    /// Collects the headings in document order, including those in footnotes, admonitions and
+    /// definition lists.
+    fn headings<'b>(blocks: &'b [Self], headings: &mut Vec<&'b Heading<'a>>) {
+        for block in blocks {
+            match block {
+                Block::Heading(heading) => headings.push(heading),
+                Block::Footnote(footnote) => Self::headings(&footnote.blocks, headings),
+                Block::Admonition(admonition) => Self::headings(&admonition.blocks, headings),
+                Block::DefinitionList(list) => list
+                    .items
+                    .iter()
+                    .flat_map(|item| &item.definitions)
+                    .for_each(|definition| Self::headings(definition, headings)),
+                _ => {}
+            }
+        }
+    }
+
+    fn headings_mut<'b>(blocks: &'b mut [Self], headings: &mut Vec<&'b mut Heading<'a>>) {
+        for block in blocks {
+            match block {
+                Block::Heading(heading) => headings.push(heading),
+                Block::Footnote(footnote) => Self::headings_mut(&mut footnote.blocks, headings),
+                Block::Admonition(admonition) => {
+                    Self::headings_mut(&mut admonition.blocks, headings)
+                }
+                Block::DefinitionList(list) => list
+                    .items
+                    .iter_mut()
+                    .flat_map(|item| &mut item.definitions)
+                    .for_each(|definition| Self::headings_mut(definition, headings)),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn parse(input: &'a str) -> IResult<&'a str, Vec<Self>> {
+        Self::parse_with(input, &Options::default())
+    }
+
+    /// Parses an HTML block, or in MDX a block of JSX or an expression.
+    fn parse_raw(input: &'a str, options: &Options) -> IResult<&'a str, Self> {
+        match options.mode {
+            Mode::Markdown => map(html::parse_block, |html| Html(html).into())(input),
+            Mode::Mdx => alt((
+                map(jsx::parse_block, |jsx| Jsx(jsx).into()),
+                map(jsx::parse_expression_block, |e| Expression(e).into()),
+            ))(input),
+        }
+    }
+
+    /// Parses a single block.
+    fn parse_block(input: &'a str, options: &Options) -> IResult<&'a str, Self> {
+        alt((
+            map(|i| Heading::parse_with(i, options), Block::from),
+            ThematicBreak::parse_into_block,
+            CodeBlock::parse_into_block,
+            map(|i| Math::parse_block(i, options), Block::from),
+            map(|i| Admonition::parse_with(i, options), Block::from),
+            |i| Self::parse_raw(i, options),
+            Definition::parse_into_block,
+            OrderedList::parse_into_block,
+            UnorderedList::parse_into_block,
+            map(|i| Footnote::parse_with(i, options), Block::from),
+            map(|i| DefinitionList::parse_with(i, options), Block::from),
+            map(|i| TextBlock::parse_with(i, options), Block::from),
+            Newline::parse_into_block,
+        ))(input)
+    }
+
+    /// Parses the blocks without making the ids of their headings unique, for the contents of
+    /// a container.
+    fn parse_blocks(input: &'a str, options: &Options) -> IResult<&'a str, Vec<Self>> {
+        let (rest, (blocks, _)) = many_till(|i| Self::parse_block(i, options), eof)(input)?;
+        Ok((rest, blocks))
+    }
+
+    pub fn parse_with(input: &'a str, options: &Options) -> IResult<&'a str, Vec<Self>> {
+        let (rest, mut blocks) = Self::parse_blocks(input, options)?;
+        Self::assign_heading_ids(&mut blocks);
+
+        Ok((rest, blocks))
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_lines() {
+        let input = r#"the quick brown fox
+jumps over a lazy dog
+"#;
+
+        let (rest, line_1) = parse_line(input).unwrap();
+        assert_eq!(line_1, "the quick brown fox");
+
+        let (rest, line_2) = parse_line(rest).unwrap();
+        assert_eq!(line_2, "jumps over a lazy dog");
+
+        assert_eq!(rest, "");
+
+        let input = "\ntest";
+
+        let (rest, _) = Newline::parse(input).unwrap();
+        assert_eq!(rest, "test");
+    }
+
+    #[test]
+    fn test_parse_heading() {
+        let (_out, heading) = Heading::parse("# h1 \n").unwrap();
+        assert_eq!(heading.level, 1);
+        assert_eq!(heading.text, "h1");
+
+        let (_, heading) = Heading::parse("###### h6").unwrap();
+        assert_eq!(heading.level, 6);
+        assert_eq!(heading.text, "h6");
+
+        let (_, heading) = Heading::parse("   ## Title ##   \n").unwrap();
+        assert_eq!(heading.level, 2);
+        assert_eq!(heading.text, "Title");
+
+        let (_, heading) = Heading::parse("# C# #\n").unwrap();
+        assert_eq!(heading.text, "C#");
+
+        let (_, heading) = Heading::parse("# Title#\n").unwrap();
+        assert_eq!(heading.text, "Title#");
+
+        let (_, heading) = Heading::parse("### ###\n").unwrap();
+        assert_eq!(heading.level, 3);
+        assert_eq!(heading.text, "");
+
+        let (_, heading) = Heading::parse("#\n").unwrap();
+        assert_eq!(heading.level, 1);
+        assert_eq!(heading.text, "");
+
+        let (_, heading) = Heading::parse("# Q&amp;A for `a\\*b` \\# tags\n").unwrap();
+        assert_eq!(heading.text_value(), "Q&A for a\\*b # tags");
+
+        assert!(Heading::parse("####### h7\n").is_err());
+        assert!(Heading::parse("#hashtag\n").is_err());
+        assert!(Heading::parse("    # indented\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_setext_heading() {
+        let (rest, heading) = Heading::parse("Title\n=====\nnext").unwrap();
+        assert_eq!(heading.level, 1);
+        assert_eq!(heading.text, "Title");
+        assert_eq!(rest, "next");
+
+        let (_, heading) = Heading::parse("Title\n---   \n").unwrap();
+        assert_eq!(heading.level, 2);
+        assert_eq!(heading.text, "Title");
+
+        let input = indoc! {"
+            A heading
+            over two lines
+            ==
+        "};
+
+        let (_, heading) = Heading::parse(input).unwrap();
+        assert_eq!(heading.level, 1);
+        assert_eq!(heading.text, "A heading\nover two lines");
+
+        assert!(Heading::parse("Title\n\n===\n").is_err());
+        assert!(Heading::parse("Title\n= =\n").is_err());
+        assert!(Heading::parse("- item\n---\n").is_err());
+        assert!(Heading::parse("===\n").is_err());
+
+        // raw HTML only interrupts the text in markdown
+        let input = "Title\n<div>\n---\n";
+        assert!(Heading::parse(input).is_err());
+
+        let options = Options {
+            mode: Mode::Mdx,
+            ..Options::default()
+        };
+
+        let (_, heading) = Heading::parse_with(input, &options).unwrap();
+        assert_eq!(heading.text, "Title\n<div>");
+    }
+
+    #[test]
+    fn test_parse_code_block() {
+        let input = r#"```
+const add = (lhs, rhs) => lhs + rhs;
+```
+"#;
+        let (_, block) = CodeBlock::parse(input).unwrap();
+        assert_eq!(block.lang, None);
+        assert_eq!(block.contents, "const add = (lhs, rhs) => lhs + rhs;");
+
+        let input = r#"```typescript
+const add = (lhs: number, rhs: number): number => lhs + rhs;
+```
+"#;
+        let (_, block) = CodeBlock::parse(input).unwrap();
+        assert_eq!(block.lang, Some("typescript"));
+        assert_eq!(
+            block.contents,
+            "const add = (lhs: number, rhs: number): number => lhs + rhs;"
+        );
+    }
+
+    #[test]
+    fn test_parse_code_block_fences() {
+        let input = indoc! {"
+            ````markdown
+            ```js
+            let multi = 'line';
+            ```
+            `````
+            after
+        "};
+
+        let (rest, block) = CodeBlock::parse(input).unwrap();
+        assert_eq!(block.lang, Some("markdown"));
+        assert_eq!(block.contents, "```js\nlet multi = 'line';\n```");
+        assert_eq!(rest, "after\n");
+
+        let (_, block) = CodeBlock::parse("~~~\n```\n~~~\n").unwrap();
+        assert_eq!(block.contents, "```");
+
+        let (_, block) = CodeBlock::parse("```\n```\n").unwrap();
+        assert_eq!(block.contents, "");
+
+        let (rest, block) = CodeBlock::parse("```\nunclosed\n\ncode").unwrap();
+        assert_eq!(block.contents, "unclosed\n\ncode");
+        assert_eq!(rest, "");
+
+        let input = "  ```\n  indented\n    more\n code\n  ```\n";
+        let (_, block) = CodeBlock::parse(input).unwrap();
+        assert_eq!(block.contents, "indented\n  more\ncode");
+
+        assert!(CodeBlock::parse("``\ncode\n``\n").is_err());
+        assert!(CodeBlock::parse("``` `inline` ```\n").is_err());
+
+        // a fence indented by four spaces is indented code instead
+        let (_, block) = CodeBlock::parse("    ```\ncode\n```\n").unwrap();
+        assert!(block.indented);
+        assert_eq!(block.contents, "```");
+    }
+
+    #[test]
+    fn test_parse_indented_code_block() {
+        let input = "    fn main() {\n\t  println!();\n\n      \n    }\n\n\ntext\n";
+
+        let (rest, block) = CodeBlock::parse(input).unwrap();
+        assert!(block.indented);
+        assert_eq!(block.lang, None);
+        assert_eq!(block.contents, "fn main() {\n  println!();\n\n  \n}");
+        assert_eq!(rest, "\n\ntext\n");
+
+        let (_, block) = CodeBlock::parse("  \tcode").unwrap();
+        assert_eq!(block.contents, "code");
+
+        assert!(CodeBlock::parse("   not code\n").is_err());
+        assert!(CodeBlock::parse("\n    code\n").is_err());
+
+        let input = indoc! {"
+            a paragraph
+                can't be interrupted
+
+                code
+            ===
+        "};
+
+        let (_, blocks) = Block::parse(input).unwrap();
+
+        assert_eq!(
+            blocks,
+            vec![
+                Block::TextBlock(TextBlock {
+                    contents: vec![
+                        TextBlockItem::Text(Text("a paragraph")),
+                        TextBlockItem::SoftBreak,
+                        TextBlockItem::Text(Text("can't be interrupted")),
+                    ],
+                }),
+                Block::CodeBlock(CodeBlock {
+                    lang: None,
+                    meta: CodeMeta::default(),
+                    contents: Cow::Borrowed("code"),
+                    indented: true,
+                }),
+                Block::TextBlock(TextBlock {
+                    contents: vec![TextBlockItem::Text(Text("==="))],
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_code_block_meta() {
+        let input = indoc! {r#"
+            ```rust title="main.rs" {1,3-5} showLineNumbers
+            fn main() {}
+            ```
+        "#};
+
+        let (_, block) = CodeBlock::parse(input).unwrap();
+        assert_eq!(block.lang, Some("rust"));
+        assert_eq!(block.meta.get("title"), Some("main.rs"));
+        assert!(block.meta.has("showLineNumbers"));
+        assert_eq!(block.meta.get("showLineNumbers"), None);
+        assert_eq!(block.meta.highlight, vec![1..=1, 3..=5]);
+
+        let (_, block) = CodeBlock::parse("```js{2} live=true caption='A b'\n```\n").unwrap();
+        assert_eq!(block.lang, Some("js"));
+        assert_eq!(block.meta.highlight, vec![2..=2]);
+        assert_eq!(
+            block.meta.attributes,
+            vec![("live", Some("true")), ("caption", Some("A b"))]
+        );
+
+        let (_, block) = CodeBlock::parse("``` {x,4}\n```\n").unwrap();
+        assert_eq!(block.lang, None);
+        assert_eq!(block.meta.highlight, vec![4..=4]);
+    }
+
+    #[test]
+    fn test_parse_link() {
+        let input = "[GitHub Pages](https://pages.github.com/)";
+
+        let (_, link) = Link::parse(input).unwrap();
+
+        assert_eq!(link.text, "GitHub Pages");
+        assert_eq!(link.url, "https://pages.github.com/");
+
+        let (_, link) = Link::parse(r#"[**a** &amp; \`b\`](/a\(1\)?x=1&amp;y=2 'it\'s')"#).unwrap();
+        assert_eq!(link.text_value(), "a & `b`");
+        assert_eq!(link.href(), "/a(1)?x=1&y=2");
+        assert_eq!(link.title_value().as_deref(), Some("it's"));
+
+        let (_, link) = Link::parse_autolink("<https://a.b/c\\d>").unwrap();
+        assert_eq!(link.href(), "https://a.b/c\\d");
+
+        let (rest, link) = Link::parse("[a [b] c](url) after").unwrap();
+        assert_eq!(link.text, "a [b] c");
+        assert_eq!(link.url, "url");
+        assert_eq!(rest, " after");
+
+        let (_, link) =
+            Link::parse("[Rust](https://en.wikipedia.org/wiki/Rust_(language))").unwrap();
+        assert_eq!(link.url, "https://en.wikipedia.org/wiki/Rust_(language)");
+
+        let (_, link) = Link::parse("[x](url \"title\")").unwrap();
+        assert_eq!(link.url, "url");
+        assert_eq!(link.title, Some("title"));
+
+        let (_, link) = Link::parse("[x]( <a url with spaces>\n  'title' )").unwrap();
+        assert_eq!(link.url, "a url with spaces");
+        assert_eq!(link.title, Some("title"));
+
+        let (_, link) = Link::parse("[x \\] y](a\\)b)").unwrap();
+        assert_eq!(link.text, "x \\] y");
+        assert_eq!(link.url, "a\\)b");
+
+        let (_, link) = Link::parse(r#"[x](url "a \" b")"#).unwrap();
+        assert_eq!(link.title, Some(r#"a \" b"#));
+
+        let (_, link) = Link::parse(r"[x](url (a \) b))").unwrap();
+        assert_eq!(link.title, Some(r"a \) b"));
+
+        let (_, link) = Link::parse("[empty]()").unwrap();
+        assert_eq!(link.url, "");
+
+        // a destination that isn't valid makes it a shortcut reference followed by text
+        let (rest, link) = Link::parse("[x](two words)").unwrap();
+        assert_eq!(link.reference, Some(Reference::Shortcut));
+        assert_eq!(rest, "(two words)");
+    }
+
+    #[test]
+    fn test_parse_reference_link() {
+        let (rest, link) = Link::parse("[text][label] after").unwrap();
+        assert_eq!(link.reference, Some(Reference::Full("label")));
+        assert_eq!(link.label(), Some("label"));
+        assert_eq!(link.url, "");
+        assert_eq!(rest, " after");
+
+        let (_, link) = Link::parse(r"[text][a\]b]").unwrap();
+        assert_eq!(link.reference, Some(Reference::Full(r"a\]b")));
+
+        let (rest, link) = Link::parse("[text][a[b]").unwrap();
+        assert_eq!(link.reference, Some(Reference::Shortcut));
+        assert_eq!(rest, "[a[b]");
+
+        let (_, link) = Link::parse("[label][]").unwrap();
+        assert_eq!(link.reference, Some(Reference::Collapsed));
+        assert_eq!(link.label(), Some("label"));
+
+        let (rest, link) = Link::parse("[label] (not a url)").unwrap();
+        assert_eq!(link.reference, Some(Reference::Shortcut));
+        assert_eq!(rest, " (not a url)");
+
+        let (_, link) = Link::parse("[inline](https://example.com)").unwrap();
+        assert_eq!(link.label(), None);
+
+        assert!(Link::parse("[^note]").is_err());
+    }
+
+    #[test]
+    fn test_parse_definition() {
+        let (rest, definition) =
+            Definition::parse("[Label]: https://example.com \"Title\"\nnext").unwrap();
+        assert_eq!(
+            definition,
+            Definition {
+                label: "Label",
+                url: "https://example.com",
+                title: Some("Title"),
+            }
+        );
+        assert_eq!(rest, "next");
+
+        let (_, definition) = Definition::parse("   [a]: <with spaces> (Paren)").unwrap();
+        assert_eq!(definition.url, "with spaces");
+        assert_eq!(definition.title, Some("Paren"));
+
+        // a title that isn't on its own is the start of the next block
+        let (rest, definition) = Definition::parse("[a]: /url\n\"title\" text\n").unwrap();
+        assert_eq!(definition.title, None);
+        assert_eq!(rest, "\"title\" text\n");
+
+        let (_, definition) = Definition::parse("[a\\]b]: /url 'it\\'s'\n").unwrap();
+        assert_eq!(definition.label, r"a\]b");
+        assert_eq!(definition.title, Some(r"it\'s"));
+
+        assert!(Definition::parse("[a]: /url \"title\" text\n").is_err());
+        assert!(Definition::parse("[^a]: footnote\n").is_err());
+        assert!(Definition::parse("[a]:\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_definition_list() {
+        let input = indoc! {"
+            Apple
+            *Malus*
+            : A fruit.
+
+                Grows on trees.
+            : A company.
+
+            Orange
+            : A colour.
+
+            Not a term
+        "};
+
+        let (_, blocks) = Block::parse(input).unwrap();
+        assert!(matches!(blocks[0], Block::TextBlock(_)));
+
+        let options = Options {
+            definition_lists: true,
+            ..Options::default()
+        };
+
+        let (rest, blocks) = Block::parse_with(input, &options).unwrap();
+        assert_eq!(rest, "");
+
+        let Block::DefinitionList(list) = &blocks[0] else {
+            panic!("expected a definition list, found {:?}", blocks[0]);
+        };
+
+        assert_eq!(list.items.len(), 2);
+
+        let apple = &list.items[0];
+        assert_eq!(
+            apple.terms,
+            vec![
+                vec![TextBlockItem::Text(Text("Apple"))],
+                vec![TextBlockItem::Emphasis(vec![TextBlockItem::Text(Text(
+                    "Malus"
+                ))])],
+            ]
+        );
+        assert_eq!(apple.definitions.len(), 2);
+        assert_eq!(apple.definitions[0].len(), 2);
+        assert_eq!(
+            apple.definitions[1],
+            vec![Block::TextBlock(TextBlock {
+                contents: vec![TextBlockItem::Text(Text("A company."))],
+            })]
+        );
+
+        assert_eq!(
+            blocks[1..],
+            [
+                Block::Newline(Newline),
+                Block::TextBlock(TextBlock {
+                    contents: vec![TextBlockItem::Text(Text("Not a term"))],
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_image() {
+        let input = "![This is an image](https://myoctocat.com/assets/images/base-octocat.svg)";
+
+        let (_, image) = Image::parse(input).unwrap();
+
+        assert_eq!(image.alt, "This is an image");
+        assert_eq!(
+            image.source,
+            "https://myoctocat.com/assets/images/base-octocat.svg"
+        );
+
+        let (_, image) = Image::parse("![a [nested] alt](<a b.png> (Title))").unwrap();
+        assert_eq!(image.alt, "a [nested] alt");
+        assert_eq!(image.source, "a b.png");
+        assert_eq!(image.title, Some("Title"));
+
+        let (_, image) =
+            Image::parse(r#"![*R&amp;D* \[1\]](a\_b&#46;png "&quot;x&quot;")"#).unwrap();
+        assert_eq!(image.alt_value(), "R&D [1]");
+        assert_eq!(image.source_value(), "a_b.png");
+        assert_eq!(image.title_value().as_deref(), Some("\"x\""));
+    }
+
+    #[test]
+    fn test_parse_unordered_list() {
+        let input = r#"- George Washington
+- John Adams 
+- Thomas Jefferson
+"#;
+
+        let (_, list) = UnorderedList::parse(input).unwrap();
+        assert_eq!(&list[0], "George Washington");
+        assert_eq!(&list[1], "John Adams");
+        assert_eq!(&list[2], "Thomas Jefferson");
+    }
+
+    #[test]
+    fn test_parse_nested_list() {
+        let input = indoc! {"
+            - Fruit
+              - Apple
+              - Pear
+                1. Conference
+            - Vegetables
+                - Carrot
+        "};
+
+        let (rest, list) = UnorderedList::parse(input).unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(list.len(), 2);
+
+        let Block::UnorderedList(fruit) = &list.items[0].children[0] else {
+            panic!("expected a nested unordered list");
+        };
+
+        assert_eq!(&fruit[0], "Apple");
+        assert_eq!(&fruit[1], "Pear");
+
+        let Block::OrderedList(pears) = &fruit.items[1].children[0] else {
+            panic!("expected a nested ordered list");
+        };
+
+        assert_eq!(&pears[0], "Conference");
+
+        let Block::UnorderedList(vegetables) = &list.items[1].children[0] else {
+            panic!("expected a nested unordered list");
+        };
+
+        assert_eq!(&vegetables[0], "Carrot");
+    }
+
+    #[test]
+    fn test_parse_ordered_list() {
+        let input = r#"1. George Washington
+2. John Adams 
+3. Thomas Jefferson
+"#;
+
+        let (_, list) = OrderedList::parse(input).unwrap();
+        assert_eq!(&list[0], "George Washington");
+        assert_eq!(&list[1], "John Adams");
+        assert_eq!(&list[2], "Thomas Jefferson");
+    }
+
+    #[test]
+    fn test_parse_list_markers() {
+        let input = indoc! {"
+            3) three
+            4) four
+            5. another list
+            * star
+            + plus
+        "};
+
+        let (_, blocks) = Block::parse(input).unwrap();
+
+        let [Block::OrderedList(three), Block::OrderedList(five), Block::UnorderedList(star), Block::UnorderedList(plus)] =
+            &blocks[..]
+        else {
+            panic!("expected four lists, found {blocks:?}");
+        };
+
+        assert_eq!((three.start, three.delimiter, three.len()), (3, ')', 2));
+        assert_eq!((five.start, five.delimiter, five.len()), (5, '.', 1));
+        assert_eq!((star.bullet, plus.bullet), ('*', '+'));
+        assert!(OrderedList::parse("1234567890. too long\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_loose_list() {
+        let input = indoc! {"
+            - tight
+              - nested
+
+              - loose nested
+            - item
+
+            - loose
+        "};
+
+        let (rest, list) = UnorderedList::parse(input).unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(list.len(), 3);
+        assert!(list.loose);
+
+        let Block::UnorderedList(nested) = &list.items[0].children[0] else {
+            panic!("expected a nested unordered list");
+        };
+
+        assert!(nested.loose);
+
+        let (_, list) = UnorderedList::parse("- a\n  - b\n\n  1. c\n- d\n").unwrap();
+        assert!(list.loose);
+        assert_eq!(list.items[0].children.len(), 2);
+
+        let (rest, list) = OrderedList::parse("1. a\n2. b\n\ntext\n").unwrap();
+        assert_eq!(rest, "\ntext\n");
+        assert!(!list.loose);
+    }
+
+    #[test]
+    fn test_parse_tasks() {
+        let input = indoc! {"
+            - [ ] incomplete item
+            - [x] completed item
+            - regular item
+              * [X] nested task
+              * [x]not a task
+            - [ ]
+        "};
+
+        let (rest, list) = UnorderedList::parse(input).unwrap();
+        assert_eq!(rest, "");
+
+        let checked: Vec<Option<bool>> = list.items.iter().map(|item| item.checked).collect();
+        assert_eq!(checked, vec![Some(false), Some(true), None, None]);
+        assert_eq!(&list[0], "incomplete item");
+        assert_eq!(&list[3], "[ ]");
+
+        let Block::UnorderedList(nested) = &list.items[2].children[0] else {
+            panic!("expected a nested unordered list");
+        };
+
+        assert_eq!(nested.items[0].checked, Some(true));
+        assert_eq!(&nested[0], "nested task");
+        assert_eq!(nested.items[1].checked, None);
+
+        let (_, list) = OrderedList::parse("1. [x] done\n2. [ ] to do\n").unwrap();
+        assert_eq!(list.items[1].checked, Some(false));
+        assert_eq!(&list[1], "to do");
+    }
+
+    #[test]
+    fn test_parse_footnote() {
+        let input = "[^note]: The note\n";
+
+        let (_, footnote) = Footnote::parse(input).unwrap();
+        assert_eq!(footnote.name, "note");
+        assert_eq!(
+            footnote.blocks,
+            vec![Block::TextBlock(TextBlock {
+                contents: vec![TextBlockItem::Text(Text("The note"))],
+            })]
+        );
+
+        let input = indoc! {"
+            [^2]: A footnote with
+              a lazy line.
+
+                A second paragraph.
+
+                - a list
+                - of items
+
+                ```rust
+                let x = 1;
+                ```
+            [^3]: Another footnote.
+        "};
+
+        let (rest, footnote) = Footnote::parse(input).unwrap();
+        assert_eq!(rest, "[^3]: Another footnote.\n");
+        assert_eq!(footnote.name, "2");
+        assert_eq!(footnote.blocks.len(), 4);
+
+        assert_eq!(
+            footnote.blocks[0],
+            Block::TextBlock(TextBlock {
+                contents: vec![
+                    TextBlockItem::Text(Text("A footnote with")),
+                    TextBlockItem::SoftBreak,
+                    TextBlockItem::Text(Text("a lazy line.")),
+                ],
+            })
+        );
+
+        let Block::UnorderedList(list) = &footnote.blocks[2] else {
+            panic!("expected a list, found {:?}", footnote.blocks[2]);
+        };
+
+        assert_eq!(list.len(), 2);
+
+        let Block::CodeBlock(code) = &footnote.blocks[3] else {
+            panic!("expected a code block, found {:?}", footnote.blocks[3]);
+        };
+
+        assert_eq!(code.lang, Some("rust"));
+        assert_eq!(code.contents, "let x = 1;");
+
+        let (rest, footnote) = Footnote::parse("[^4]: Text\n\nAfter\n").unwrap();
+        assert_eq!(rest, "\nAfter\n");
+        assert_eq!(footnote.blocks.len(), 1);
+
+        // a reference at the start of a line continues the footnote lazily
+        let (rest, footnote) = Footnote::parse("[^1]: First line\n[^2] continues here.\n").unwrap();
+        assert_eq!(rest, "");
+        assert_eq!(
+            footnote.blocks,
+            vec![Block::TextBlock(TextBlock {
+                contents: vec![
+                    TextBlockItem::Text(Text("First line")),
+                    TextBlockItem::SoftBreak,
+                    TextBlockItem::FootnoteRef(FootnoteRef {
+                        name: "2",
+                        number: None,
+                        occurrence: 0,
+                    }),
+                    TextBlockItem::Text(Text(" continues here.")),
+                ],
+            })]
+        );
+
+        let input = indoc! {"
+            [^5]: Code, HTML and math.
+
+                    indented code
+                      more
+
+                <div>
+                </div>
+
+                $$
+                x^2
+                $$
+        "};
+
+        let options = Options {
+            math: true,
+            ..Options::default()
+        };
+
+        let (rest, footnote) = Footnote::parse_with(input, &options).unwrap();
+        assert_eq!(rest, "");
+
+        let Block::CodeBlock(code) = &footnote.blocks[1] else {
+            panic!("expected a code block, found {:?}", footnote.blocks[1]);
+        };
+
+        assert!(code.indented);
+        assert_eq!(code.contents, "indented code\n  more");
+        assert!(matches!(footnote.blocks[2], Block::Html(_)));
+        assert!(matches!(
+            footnote.blocks[3],
+            Block::Math(Math {
+                contents: "x^2",
+                display: true,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_text_block() {
+        let text = indoc! {"
+            the block
+            of text
+
+        "};
+
+        let (_, block) = TextBlock::parse(text).unwrap();
+        assert_eq!(
+            block.contents,
+            vec![
+                TextBlockItem::Text(Text("the block")),
+                TextBlockItem::SoftBreak,
+                TextBlockItem::Text(Text("of text")),
+            ]
+        );
+
+        let text = indoc! {"
+            text with [inline](https://google.com) link
+
+        "};
+
+        let (_, block) = TextBlock::parse(text).unwrap();
+
+        assert_eq!(
+            block.contents,
+            vec![
+                TextBlockItem::Text(Text("text with ")),
+                TextBlockItem::Link(Link {
+                    text: "inline",
+                    url: "https://google.com",
+                    title: None,
+                    reference: None,
+                    autolink: false,
+                }),
+                TextBlockItem::Text(Text(" link"))
+            ]
+        );
+
+        // only non-empty list items, and ordered ones starting at 1, interrupt a paragraph
+        let text = indoc! {"
+            The year was
+            1984. A good year.
+            +
+            1. one
+        "};
+
+        let (_, blocks) = Block::parse(text).unwrap();
+
+        assert_eq!(
+            blocks,
+            vec![
+                Block::TextBlock(TextBlock {
+                    contents: vec![
+                        TextBlockItem::Text(Text("The year was")),
+                        TextBlockItem::SoftBreak,
+                        TextBlockItem::Text(Text("1984. A good year.")),
+                        TextBlockItem::SoftBreak,
+                        TextBlockItem::Text(Text("+")),
+                    ],
+                }),
+                Block::OrderedList(OrderedList {
+                    items: vec![ListItem {
+                        text: Cow::Borrowed("one"),
+                        checked: None,
+                        children: vec![],
+                    }],
+                    start: 1,
+                    delimiter: '.',
+                    loose: false,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_inline_image() {
+        let text = "Wow! text ![icon](a.png) more\n";
+
+        let (_, block) = TextBlock::parse(text).unwrap();
+
+        assert_eq!(
+            block.contents,
+            vec![
+                TextBlockItem::Text(Text("Wow! text ")),
+                TextBlockItem::Image(Image {
+                    alt: "icon",
+                    source: "a.png",
+                    title: None,
+                    reference: None,
+                }),
+                TextBlockItem::Text(Text(" more")),
+            ]
+        );
+
+        let (_, blocks) = Block::parse("![only](image.png)\n\n![a](b) text\n").unwrap();
+
+        assert_eq!(
+            blocks[0],
+            Block::Image(Image {
+                alt: "only",
+                source: "image.png",
+                title: None,
+                reference: None,
+            })
+        );
+
+        assert!(matches!(&blocks[1], Block::TextBlock(block) if block.len() == 2));
+    }
+
+    #[test]
+    fn test_parse_block_link() {
+        let (_, blocks) = Block::parse("[only](a.html)\n\n[Note] more text\n").unwrap();
+
+        assert_eq!(
+            blocks[0],
+            Block::Link(Link {
+                text: "only",
+                url: "a.html",
+                title: None,
+                reference: None,
+                autolink: false,
+            })
+        );
+
+        assert_eq!(
+            blocks[1],
+            Block::TextBlock(TextBlock {
+                contents: vec![
+                    TextBlockItem::Link(Link {
+                        text: "Note",
+                        url: "",
+                        title: None,
+                        reference: Some(Reference::Shortcut),
+                        autolink: false,
+                    }),
+                    TextBlockItem::Text(Text(" more text")),
+                ],
+            })
+        );
+        assert_eq!(blocks.len(), 2);
+    }
+
+    fn inline<'a>(input: &'a str, options: &Options) -> Vec<TextBlockItem<'a>> {
+        let (_, block) = TextBlock::parse_with(input, options).unwrap();
+        block.contents
+    }
+
+    #[test]
+    fn test_parse_emphasis() {
+        use TextBlockItem::{Emphasis, Strong};
+
+        assert_eq!(
+            inline("*a* __b__ ***c***", &Options::default()),
+            vec![
+                Emphasis(vec![TextBlockItem::Text(Text("a"))]),
+                TextBlockItem::Text(Text(" ")),
+                Strong(vec![TextBlockItem::Text(Text("b"))]),
+                TextBlockItem::Text(Text(" ")),
+                Emphasis(vec![Strong(vec![TextBlockItem::Text(Text("c"))])]),
+            ]
+        );
+
+        assert_eq!(
+            inline("**a* _b __c__ d_ *e_", &Options::default()),
+            vec![
+                TextBlockItem::Text(Text("*")),
+                Emphasis(vec![TextBlockItem::Text(Text("a"))]),
+                TextBlockItem::Text(Text(" ")),
+                Emphasis(vec![
+                    TextBlockItem::Text(Text("b ")),
+                    Strong(vec![TextBlockItem::Text(Text("c"))]),
+                    TextBlockItem::Text(Text(" d")),
+                ]),
+                TextBlockItem::Text(Text(" *e_")),
+            ]
+        );
+
+        assert_eq!(
+            inline("**bold *italic* [link](url)**", &Options::default()),
+            vec![Strong(vec![
+                TextBlockItem::Text(Text("bold ")),
+                Emphasis(vec![TextBlockItem::Text(Text("italic"))]),
+                TextBlockItem::Text(Text(" ")),
+                TextBlockItem::Link(Link {
+                    text: "link",
+                    url: "url",
+                    title: None,
+                    reference: None,
+                    autolink: false,
+                }),
+            ])]
+        );
+
+        for text in [
+            "2 * 3 * 4",
+            "snake_case_name",
+            "* not emphasis*",
+            "**unclosed",
+        ] {
+            assert_eq!(
+                inline(text, &Options::default()),
+                vec![TextBlockItem::Text(Text(text))]
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_unmatched_delimiters() {
+        let options = Options {
+            strikethrough: true,
+            highlight: true,
+            superscript: true,
+            subscript: true,
+            ..Options::default()
+        };
+
+        // long runs of delimiters without a closer are left as text without backtracking
+        for text in ["*a ", "**a ", "_a *b ", "~~a ==b ^c ~d "] {
+            let text = text.repeat(5000);
+            let text = text.trim_end();
+            assert_eq!(
+                inline(text, &options),
+                vec![TextBlockItem::Text(Text(text))]
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_inline_code() {
+        assert_eq!(
+            inline("use `*a*` or `` ` `` and `unclosed", &Options::default()),
+            vec![
+                TextBlockItem::Text(Text("use ")),
+                TextBlockItem::Code(InlineCode("*a*")),
+                TextBlockItem::Text(Text(" or ")),
+                TextBlockItem::Code(InlineCode("`")),
+                TextBlockItem::Text(Text(" and `unclosed")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_escapes() {
+        let items = inline(
+            r"\*not emphasis\* \[not a link\] *a\*b* \\*c*",
+            &Options::default(),
+        );
+
+        assert_eq!(
+            items,
+            vec![
+                TextBlockItem::Text(Text(r"\*not emphasis\* \[not a link\] ")),
+                TextBlockItem::Emphasis(vec![TextBlockItem::Text(Text(r"a\*b"))]),
+                TextBlockItem::Text(Text(r" \\")),
+                TextBlockItem::Emphasis(vec![TextBlockItem::Text(Text("c"))]),
+            ]
+        );
+
+        let TextBlockItem::Text(text) = &items[0] else {
+            panic!("expected text");
+        };
+
+        assert_eq!(text.value(), "*not emphasis* [not a link] ");
+        assert_eq!(Text(r" \\").value(), r" \");
+    }
+
+    #[test]
+    fn test_text_value() {
+        assert!(matches!(Text("plain").value(), Cow::Borrowed("plain")));
+        assert_eq!(
+            Text("&copy; 2024 &amp; &#x27;quoted&#39; &unknown; & \\a é\\").value(),
+            "\u{a9} 2024 & 'quoted' &unknown; & \\a é\\"
+        );
+    }
+
+    #[test]
+    fn test_parse_line_breaks() {
+        use TextBlockItem::{HardBreak, SoftBreak};
+
+        let text = "soft \n  hard  \nescaped\\\n*across\nlines*\\\\\nlast  ";
+
+        assert_eq!(
+            inline(text, &Options::default()),
+            vec![
+                TextBlockItem::Text(Text("soft")),
+                SoftBreak,
+                TextBlockItem::Text(Text("hard")),
+                HardBreak,
+                TextBlockItem::Text(Text("escaped")),
+                HardBreak,
+                TextBlockItem::Emphasis(vec![
+                    TextBlockItem::Text(Text("across")),
+                    SoftBreak,
+                    TextBlockItem::Text(Text("lines")),
+                ]),
+                TextBlockItem::Text(Text("\\\\")),
+                SoftBreak,
+                TextBlockItem::Text(Text("last")),
+            ]
+        );
+
+        assert_eq!(plain_text("a\nb  \nc"), "a b\nc");
+
+        let options = Options {
+            strikethrough: true,
+            highlight: true,
+            ..Options::default()
+        };
+
+        assert_eq!(plain_text("~~a~~ ==b=="), "~~a~~ ==b==");
+        assert_eq!(plain_text_with("~~a~~ [==b==](c)", &options), "a b");
+    }
+
+    #[test]
+    fn test_parse_extension_spans() {
+        use TextBlockItem::{Highlight, Strikethrough, Strong, Subscript, Superscript};
+
+        let text = "~~old **bold**~~ ==new== x^2^ H~2~O a^not super^";
+
+        assert_eq!(
+            inline(text, &Options::default()),
+            vec![
+                TextBlockItem::Text(Text("~~old ")),
+                Strong(vec![TextBlockItem::Text(Text("bold"))]),
+                TextBlockItem::Text(Text("~~ ==new== x^2^ H~2~O a^not super^")),
+            ]
+        );
+
+        let options = Options {
+            strikethrough: true,
+            highlight: true,
+            superscript: true,
+            subscript: true,
+            ..Options::default()
+        };
+
+        assert_eq!(
+            inline(text, &options),
+            vec![
+                Strikethrough(vec![
+                    TextBlockItem::Text(Text("old ")),
+                    Strong(vec![TextBlockItem::Text(Text("bold"))]),
+                ]),
+                TextBlockItem::Text(Text(" ")),
+                Highlight(vec![TextBlockItem::Text(Text("new"))]),
+                TextBlockItem::Text(Text(" x")),
+                Superscript(vec![TextBlockItem::Text(Text("2"))]),
+                TextBlockItem::Text(Text(" H")),
+                Subscript(vec![TextBlockItem::Text(Text("2"))]),
+                TextBlockItem::Text(Text("O a^not super^")),
+            ]
+        );
+
+        assert_eq!(
+            inline("**~~[x](y)~~**", &options),
+            vec![Strong(vec![Strikethrough(vec![TextBlockItem::Link(
+                Link {
+                    text: "x",
+                    url: "y",
+                    title: None,
+                    reference: None,
+                    autolink: false,
+                }
+            )])])]
+        );
     }
-}
 
-impl<'a> From<Footnote<'a>> for Block<'a> {
-    fn from(footnote: Footnote<'a>) -> Self {
-        Block::Footnote(footnote)
-    }
-}
+    #[test]
+    fn test_parse_math() {
+        let options = Options {
+            math: true,
+            ..Options::default()
+        };
 
-impl<'a> From<TextBlock<'a>> for Block<'a> {
-    fn from(text_block: TextBlock<'a>) -> Self {
-        Block::TextBlock(text_block)
-    }
-}
+        let math = |contents, display| TextBlockItem::Math(Math { contents, display });
 
-impl<'a> From<Newline> for Block<'a> {
-    fn from(nl: Newline) -> Self {
-        Block::Newline(nl)
-    }
-}
+        assert_eq!(
+            inline(r"Area $\pi r^2$, $$\sum_i x_i$$ and $\$5$.", &options),
+            vec![
+                TextBlockItem::Text(Text("Area ")),
+                math(r"\pi r^2", false),
+                TextBlockItem::Text(Text(", ")),
+                math(r"\sum_i x_i", true),
+                TextBlockItem::Text(Text(" and ")),
+                math(r"\$5", false),
+                TextBlockItem::Text(Text(".")),
+            ]
+        );
 
-pub trait ParseIntoBlock<'a>: Parse<'a> {
-    fn parse_into_block(input: &'a str) -> IResult<&'a str, Block>;
-}
+        for text in ["It costs $5 and $10.", "$ x$", "$x $", "$x$5", r"\$x$"] {
+            assert_eq!(
+                inline(text, &options),
+                vec![TextBlockItem::Text(Text(text))]
+            );
+        }
 
-impl<'a, T> ParseIntoBlock<'a> for T
-where
-    T: Parse<'a> + Into<Block<'a>>,
-{
-    fn parse_into_block(input: &'a str) -> IResult<&'a str, Block<'a>> {
-        let (rest, out) = Self::parse(input)?;
-        let out = out.into();
-        Ok((rest, out))
-    }
-}
+        assert_eq!(
+            inline("$x$", &Options::default()),
+            vec![TextBlockItem::Text(Text("$x$"))]
+        );
 
-impl<'a> Block<'a> {
-    pub fn parse(input: &'a str) -> IResult<&str, Vec<Self>> {
-        let (rest, (blocks, _)) = many_till(
-            alt((
-                Heading::parse_into_block,
-                CodeBlock::parse_into_block,
-                Link::parse_into_block,
-                Image::parse_into_block,
-                Link::parse_into_block,
-                Image::parse_into_block,
-                OrderedList::parse_into_block,
-                UnorderedList::parse_into_block,
-                TaskList::parse_into_block,
-                Footnote::parse_into_block,
-                TextBlock::parse_into_block,
-                Newline::parse_into_block,
-            )),
-            eof,
-        )(input)?;
+        let input = indoc! {r"
+            $$
+            \frac{a}{b}
+            $$
+            $$ e^{i\pi} + 1 = 0 $$
 
-        Ok((rest, blocks))
-    }
-}
+            {props.value}
+        "};
 
-#[cfg(test)]
-mod test_parse {
-    use super::*;
-    use indoc::indoc;
+        let options = Options {
+            math: true,
+            mode: Mode::Mdx,
+            ..Options::default()
+        };
 
-    #[test]
-    fn test_parse_lines() {
-        let input = r#"the quick brown fox
-jumps over a lazy dog
-"#;
+        let (_, blocks) = Block::parse_with(input, &options).unwrap();
 
-        let (rest, line_1) = parse_line(input).unwrap();
-        assert_eq!(line_1, "the quick brown fox");
+        assert_eq!(
+            blocks,
+            vec![
+                Block::Math(Math {
+                    contents: r"\frac{a}{b}",
+                    display: true,
+                }),
+                Block::Math(Math {
+                    contents: r"e^{i\pi} + 1 = 0",
+                    display: true,
+                }),
+                Block::Newline(Newline),
+                Block::Expression(Expression("props.value")),
+            ]
+        );
 
-        let (rest, line_2) = parse_line(rest).unwrap();
-        assert_eq!(line_2, "jumps over a lazy dog");
+        let input = "$$x$$ is display math in a sentence.\n\nLater paragraph\n\n$$\ny\n$$\n";
+        let (_, blocks) = Block::parse_with(input, &options).unwrap();
 
-        assert_eq!(rest, "");
+        assert_eq!(
+            blocks,
+            vec![
+                Block::TextBlock(TextBlock {
+                    contents: vec![
+                        math("x", true),
+                        TextBlockItem::Text(Text(" is display math in a sentence.")),
+                    ],
+                }),
+                Block::TextBlock(TextBlock {
+                    contents: vec![TextBlockItem::Text(Text("Later paragraph"))],
+                }),
+                Block::Math(Math {
+                    contents: "y",
+                    display: true,
+                }),
+            ]
+        );
 
-        let input = "\ntest";
+        let (_, blocks) = Block::parse_with("$$\nx\n\ny\n$$\n", &options).unwrap();
+        assert!(blocks.iter().all(|block| !matches!(block, Block::Math(_))));
 
-        let (rest, _) = Newline::parse(input).unwrap();
-        assert_eq!(rest, "test");
+        let mdx = inline("Both $\\{x\\}$ and {x}", &options);
+        assert_eq!(mdx[1], math("\\{x\\}", false));
+        assert_eq!(mdx[3], TextBlockItem::Expression(Expression("x")));
     }
 
     #[test]
-    fn test_parse_heading() {
-        let (_out, heading) = Heading::parse("# h1 \n").unwrap();
-        assert_eq!(heading.level, 1);
-        assert_eq!(heading.text, "h1");
-    }
+    fn test_parse_admonitions() {
+        let input = indoc! {"
+            ::::note[Nested *callouts*]
+            A note.
+
+            :::tip
+            - a tip
+            :::
+            ::::
+
+            > [!warning]
+            > Alerts span lines,\\
+            > with **breaks**.
+            >
+            > - and
+            > - lists
+            Not part of the alert.
+        "};
 
-    #[test]
-    fn test_parse_code_block() {
-        let input = r#"```
-const add = (lhs, rhs) => lhs + rhs;
-```
-"#;
-        let (_, block) = CodeBlock::parse(input).unwrap();
-        assert_eq!(block.lang, None);
-        assert_eq!(block.contents, "const add = (lhs, rhs) => lhs + rhs;");
+        let (_, blocks) = Block::parse(input).unwrap();
+        assert!(matches!(blocks[0], Block::TextBlock(_)));
 
-        let input = r#"```typescript
-const add = (lhs: number, rhs: number): number => lhs + rhs;
-```
-"#;
-        let (_, block) = CodeBlock::parse(input).unwrap();
-        assert_eq!(block.lang, Some("typescript"));
+        let options = Options {
+            admonitions: true,
+            ..Options::default()
+        };
+
+        let (rest, blocks) = Block::parse_with(input, &options).unwrap();
+        assert_eq!(rest, "");
+
+        let Block::Admonition(note) = &blocks[0] else {
+            panic!("expected an admonition, found {:?}", blocks[0]);
+        };
+
+        assert_eq!((note.kind, note.title), ("note", Some("Nested *callouts*")));
+        assert_eq!(note.blocks.len(), 2);
         assert_eq!(
-            block.contents,
-            "const add = (lhs: number, rhs: number): number => lhs + rhs;"
+            note.blocks[1],
+            Block::Admonition(Admonition {
+                kind: "tip",
+                title: None,
+                blocks: vec![Block::UnorderedList(UnorderedList {
+                    items: vec!["a tip".into()],
+                    bullet: '-',
+                    loose: false,
+                })],
+            })
         );
-    }
 
-    #[test]
-    fn test_parse_link() {
-        let input = "[GitHub Pages](https://pages.github.com/)";
+        assert_eq!(blocks[1], Block::Newline(Newline));
 
-        let (_, link) = Link::parse(input).unwrap();
+        assert_eq!(
+            blocks[2],
+            Block::Admonition(Admonition {
+                kind: "warning",
+                title: None,
+                blocks: vec![
+                    Block::TextBlock(TextBlock {
+                        contents: vec![
+                            TextBlockItem::Text(Text("Alerts span lines,")),
+                            TextBlockItem::HardBreak,
+                            TextBlockItem::Text(Text("with ")),
+                            TextBlockItem::Strong(vec![TextBlockItem::Text(Text("breaks"))]),
+                            TextBlockItem::Text(Text(".")),
+                        ],
+                    }),
+                    Block::UnorderedList(UnorderedList {
+                        items: vec!["and".into(), "lists".into()],
+                        bullet: '-',
+                        loose: false,
+                    }),
+                ],
+            })
+        );
 
-        assert_eq!(link.text, "GitHub Pages");
-        assert_eq!(link.url, "https://pages.github.com/");
-    }
+        assert_eq!(
+            blocks[3],
+            Block::TextBlock(TextBlock {
+                contents: vec![TextBlockItem::Text(Text("Not part of the alert."))],
+            })
+        );
 
-    #[test]
-    fn test_parse_image() {
-        let input = "![This is an image](https://myoctocat.com/assets/images/base-octocat.svg)";
+        let input = indoc! {"
+            :::note
+            ```
+            :::
+            ```
+            :::
+
+            > [!TIP]
+            > ## A heading
+            > ```sh
+            > cargo test
+            >
+            > ```
+            > $$x$$
+        "};
 
-        let (_, image) = Image::parse(input).unwrap();
+        let (_, blocks) = Block::parse_with(input, &options).unwrap();
+
+        let code = |lang, contents: &'static str| {
+            Block::CodeBlock(CodeBlock {
+                lang,
+                meta: CodeMeta::default(),
+                contents: contents.into(),
+                indented: false,
+            })
+        };
 
-        assert_eq!(image.alt, "This is an image");
         assert_eq!(
-            image.source,
-            "https://myoctocat.com/assets/images/base-octocat.svg"
+            blocks[0],
+            Block::Admonition(Admonition {
+                kind: "note",
+                title: None,
+                blocks: vec![code(None, ":::")],
+            })
+        );
+
+        let Block::Admonition(tip) = &blocks[2] else {
+            panic!("expected an admonition, found {:?}", blocks[2]);
+        };
+
+        assert!(matches!(tip.blocks[0], Block::Heading(_)));
+        assert_eq!(tip.blocks[1], code(Some("sh"), "cargo test\n"));
+        assert_eq!(
+            tip.blocks[2],
+            Block::TextBlock(TextBlock {
+                contents: vec![TextBlockItem::Text(Text("$$x$$"))],
+            })
+        );
+        assert_eq!(blocks.len(), 3);
+
+        // lists within an alert nest and are loose like lists at the top level
+        let input = indoc! {"
+            > [!NOTE]
+            > - parent
+            >   - child
+            > - sibling
+
+            > [!TIP]
+            > 1. a
+            >
+            > 2. b
+        "};
+
+        let (_, blocks) = Block::parse_with(input, &options).unwrap();
+
+        assert_eq!(
+            blocks[0],
+            Block::Admonition(Admonition {
+                kind: "NOTE",
+                title: None,
+                blocks: vec![Block::UnorderedList(UnorderedList {
+                    items: vec![
+                        ListItem {
+                            text: Cow::Borrowed("parent"),
+                            checked: None,
+                            children: vec![Block::UnorderedList(UnorderedList {
+                                items: vec![ListItem::from("child")],
+                                bullet: '-',
+                                loose: false,
+                            })],
+                        },
+                        ListItem::from("sibling"),
+                    ],
+                    bullet: '-',
+                    loose: false,
+                })],
+            })
+        );
+
+        assert_eq!(
+            blocks[2],
+            Block::Admonition(Admonition {
+                kind: "TIP",
+                title: None,
+                blocks: vec![Block::OrderedList(OrderedList {
+                    items: vec![ListItem::from("a"), ListItem::from("b")],
+                    start: 1,
+                    delimiter: '.',
+                    loose: true,
+                })],
+            })
         );
+
+        let (_, blocks) = Block::parse_with("> [!SIDENOTE]\n> text\n", &options).unwrap();
+        assert!(matches!(blocks[0], Block::TextBlock(_)));
     }
 
     #[test]
-    fn test_parse_unordered_list() {
-        let input = r#"- George Washington
-- John Adams 
-- Thomas Jefferson
-"#;
+    fn test_parse_autolinks() {
+        let text = "See <https://example.com>, www.example.com/a). or mail me@example.com.\n";
 
-        let (_, list) = UnorderedList::parse(input).unwrap();
-        assert_eq!(&list[0], "George Washington");
-        assert_eq!(&list[1], "John Adams");
-        assert_eq!(&list[2], "Thomas Jefferson");
+        let (_, block) = TextBlock::parse(text).unwrap();
+
+        let links: Vec<(&str, Cow<str>)> = block
+            .contents
+            .iter()
+            .filter_map(|item| match item {
+                TextBlockItem::Link(link) if link.autolink => Some((link.text, link.href())),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            links,
+            vec![
+                ("https://example.com", Cow::Borrowed("https://example.com")),
+                (
+                    "www.example.com/a",
+                    Cow::Borrowed("http://www.example.com/a")
+                ),
+                ("me@example.com", Cow::Borrowed("mailto:me@example.com")),
+            ]
+        );
+
+        assert_eq!(block.contents[2], TextBlockItem::Text(Text(", ")));
+        assert_eq!(block.contents[4], TextBlockItem::Text(Text("). or mail ")));
+        assert_eq!(block.contents[6], TextBlockItem::Text(Text(".")));
+
+        let (_, block) = TextBlock::parse("notawww.example.com <not a link>\n").unwrap();
+        assert_eq!(
+            block.contents,
+            vec![
+                TextBlockItem::Text(Text("notawww.example.com ")),
+                TextBlockItem::Html(Html("<not a link>")),
+            ]
+        );
     }
 
     #[test]
-    fn test_parse_ordered_list() {
-        let input = r#"1. George Washington
-2. John Adams 
-3. Thomas Jefferson
-"#;
+    fn test_parse_block_headings() {
+        let input = indoc! {"
+            #hashtag
+            # Heading
+            Setext
+            ------
+            ####### not a heading"};
 
-        let (_, list) = OrderedList::parse(input).unwrap();
-        assert_eq!(&list[0], "George Washington");
-        assert_eq!(&list[1], "John Adams");
-        assert_eq!(&list[2], "Thomas Jefferson");
+        let (_, blocks) = Block::parse(input).unwrap();
+
+        assert_eq!(
+            blocks,
+            vec![
+                Block::TextBlock(TextBlock {
+                    contents: vec![TextBlockItem::Text(Text("#hashtag"))],
+                }),
+                Block::Heading(Heading {
+                    level: 1,
+                    text: "Heading",
+                    id: HeadingId::Slug("heading".to_string()),
+                }),
+                Block::Heading(Heading {
+                    level: 2,
+                    text: "Setext",
+                    id: HeadingId::Slug("setext".to_string()),
+                }),
+                Block::TextBlock(TextBlock {
+                    contents: vec![TextBlockItem::Text(Text("####### not a heading"))],
+                }),
+            ]
+        );
     }
 
     #[test]
-    fn test_parse_tasks() {
-        let input = "- [ ] incomplete item\n";
-
-        let (_, task) = Task::parse(input).unwrap();
-        assert_eq!(task.completed, false);
-        assert_eq!(task.text, "incomplete item");
+    fn test_parse_raw_html() {
+        let input = indoc! {"
+            A paragraph
+            <details>
+            <summary>More</summary>
+
+            Some <kbd>Ctrl</kbd> {text} <!-- hidden -->
+            <Callout>
+            # Heading
+            </Callout>
+        "};
 
-        let input = "- [x] completed item\n";
+        let (_, blocks) = Block::parse(input).unwrap();
 
-        let (_, task) = Task::parse(input).unwrap();
-        assert_eq!(task.completed, true);
-        assert_eq!(task.text, "completed item");
+        assert_eq!(
+            blocks[..3],
+            [
+                Block::TextBlock(TextBlock {
+                    contents: vec![TextBlockItem::Text(Text("A paragraph"))],
+                }),
+                Block::Html(Html("<details>\n<summary>More</summary>")),
+                Block::Newline(Newline),
+            ]
+        );
 
-        let input = r#"- [ ] incomplete item
-- [x] completed item
-"#;
-        let (_, list) = TaskList::parse(input).unwrap();
-        assert_eq!(list.len(), 2);
-    }
+        assert_eq!(
+            blocks[3],
+            Block::TextBlock(TextBlock {
+                contents: vec![
+                    TextBlockItem::Text(Text("Some ")),
+                    TextBlockItem::Html(Html("<kbd>")),
+                    TextBlockItem::Text(Text("Ctrl")),
+                    TextBlockItem::Html(Html("</kbd>")),
+                    TextBlockItem::Text(Text(" {text} ")),
+                    TextBlockItem::Html(Html("<!-- hidden -->")),
+                    TextBlockItem::SoftBreak,
+                    TextBlockItem::Html(Html("<Callout>")),
+                ],
+            })
+        );
 
-    #[test]
-    fn test_parse_footnote() {
-        let input = "[^note]: The note\n";
+        assert_eq!(blocks[5], Block::Html(Html("</Callout>")));
 
-        let (_, footnote) = Footnote::parse(input).unwrap();
-        assert_eq!(footnote.name, "note");
-        assert_eq!(footnote.text, vec!["The note"]);
+        let options = Options {
+            mode: Mode::Mdx,
+            ..Options::default()
+        };
 
-        let input = indoc! {r#"
-            [^2]: Every new line should be prefixed with 2 spaces.
-              This allows you to have a footnote with multiple lines.
-        "#};
+        let (_, blocks) =
+            Block::parse_with(&input[input.find("Some").unwrap()..], &options).unwrap();
 
-        let (_, footnote) = Footnote::parse(input).unwrap();
-        assert_eq!(footnote.name, "2");
         assert_eq!(
-            footnote.text,
+            blocks,
             vec![
-                "Every new line should be prefixed with 2 spaces.",
-                "This allows you to have a footnote with multiple lines."
+                Block::TextBlock(TextBlock {
+                    contents: vec![
+                        TextBlockItem::Text(Text("Some ")),
+                        TextBlockItem::Jsx(Jsx("<kbd>")),
+                        TextBlockItem::Text(Text("Ctrl")),
+                        TextBlockItem::Jsx(Jsx("</kbd>")),
+                        TextBlockItem::Text(Text(" ")),
+                        TextBlockItem::Expression(Expression("text")),
+                        TextBlockItem::Text(Text(" <!-- hidden -->")),
+                        TextBlockItem::SoftBreak,
+                        TextBlockItem::Jsx(Jsx("<Callout>")),
+                    ],
+                }),
+                Block::Heading(Heading {
+                    level: 1,
+                    text: "Heading",
+                    id: HeadingId::Slug("heading".to_string()),
+                }),
+                Block::Jsx(Jsx("</Callout>")),
             ]
         );
     }
 
     #[test]
-    fn test_parse_text_block() {
-        let text = indoc! {"
-            the block
-            of text
+    fn test_heading_ids() {
+        let (_, heading) = Heading::parse("## Getting Started!\n").unwrap();
+        assert_eq!(heading.id, HeadingId::Slug("getting-started".to_string()));
+
+        let (_, heading) = Heading::parse("## Title {#custom-id}\n").unwrap();
+        assert_eq!(heading.text, "Title");
+        assert_eq!(heading.id, HeadingId::Custom("custom-id"));
+
+        let (_, heading) = Heading::parse("## Title ## {#custom-id}\n").unwrap();
+        assert_eq!(heading.text, "Title");
+        assert_eq!(heading.id.as_str(), "custom-id");
+
+        let (_, heading) = Heading::parse("Title {#setext}\n===\n").unwrap();
+        assert_eq!(heading.text, "Title");
+        assert_eq!(heading.id.as_str(), "setext");
+
+        let (_, heading) = Heading::parse("# Set up [Cargo](https://crates.io)[^1]\n").unwrap();
+        assert_eq!(heading.id.as_str(), "set-up-cargo");
+
+        let (_, heading) = Heading::parse("# Not an id{#id}\n").unwrap();
+        assert_eq!(heading.text, "Not an id{#id}");
+        assert_eq!(heading.id.as_str(), "not-an-idid");
 
+        let input = indoc! {"
+            # Usage
+
+            ## Usage
+
+            ## Options {#usage-1}
+
+            ## Usage
         "};
 
-        let (_, block) = TextBlock::parse(text).unwrap();
-        let expected = indoc! {"
-            the block
-            of text"};
-        assert_eq!(block.len(), 1);
-        assert_eq!(block.contents[0], TextBlockItem::Text(Text(expected)));
+        let (_, blocks) = Block::parse(input).unwrap();
 
-        let text = indoc! {"
-            text with [inline](https://google.com) link
+        let ids: Vec<String> = blocks
+            .iter()
+            .filter_map(|block| match block {
+                Block::Heading(heading) => Some(heading.id.to_string()),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(ids, vec!["usage", "usage-2", "usage-1", "usage-3"]);
+
+        let options = Options {
+            admonitions: true,
+            ..Options::default()
+        };
+
+        let (_, blocks) =
+            Block::parse_with("## Usage\n\n:::note\n## Usage\n:::\n", &options).unwrap();
+
+        let Block::Admonition(note) = &blocks[2] else {
+            panic!("expected an admonition, found {:?}", blocks[2]);
+        };
+
+        let Block::Heading(heading) = &note.blocks[0] else {
+            panic!("expected a heading, found {:?}", note.blocks[0]);
+        };
+
+        assert_eq!(heading.id.as_str(), "usage-1");
+    }
+
+    #[test]
+    fn test_parse_thematic_break() {
+        for input in ["---\n", "***", "___\n", " - - -\n", "*\t*  * *\n", "-----"] {
+            assert_eq!(
+                ThematicBreak::parse(input),
+                Ok(("", ThematicBreak)),
+                "{}",
+                input
+            );
+        }
+
+        for input in ["--\n", "- - a\n", "*-*\n", "    ---\n", "\n"] {
+            assert!(ThematicBreak::parse(input).is_err(), "{}", input);
+        }
 
+        let input = indoc! {"
+            - item
+            - - -
+            text
+            ***
+            Setext
+            ---
         "};
 
-        let (_, block) = TextBlock::parse(text).unwrap();
+        let (_, blocks) = Block::parse(input).unwrap();
 
         assert_eq!(
-            block.contents,
+            blocks,
             vec![
-                TextBlockItem::Text(Text("text with ")),
-                TextBlockItem::Link(Link {
-                    text: "inline",
-                    url: "https://google.com"
+                Block::UnorderedList(UnorderedList {
+                    items: vec!["item".into()],
+                    bullet: '-',
+                    loose: false,
+                }),
+                Block::ThematicBreak(ThematicBreak),
+                Block::TextBlock(TextBlock {
+                    contents: vec![TextBlockItem::Text(Text("text"))],
+                }),
+                Block::ThematicBreak(ThematicBreak),
+                Block::Heading(Heading {
+                    level: 2,
+                    text: "Setext",
+                    id: HeadingId::Slug("setext".to_string()),
                 }),
-                TextBlockItem::Text(Text(" link"))
             ]
         );
     }
@@ -719,15 +4308,20 @@ const add = (lhs: number, rhs: number): number => lhs + rhs;
                     contents: vec![TextBlockItem::Text(Text("some text",),),],
                 },),
                 Block::UnorderedList(UnorderedList {
-                    items: vec!["list", "list",],
+                    items: vec!["list".into(), "list".into()],
+                    bullet: '-',
+                    loose: false,
                 },),
                 Block::Newline(Newline,),
                 Block::Footnote(Footnote {
                     name: "1",
-                    text: vec!["note",],
+                    blocks: vec![Block::TextBlock(TextBlock {
+                        contents: vec![TextBlockItem::Text(Text("note"))],
+                    })],
+                    number: None,
+                    references: 0,
                 },),
             ]
         );
     }
 }
-
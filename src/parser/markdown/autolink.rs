@@ -0,0 +1,220 @@
+use nom::{
+    bytes::complete::{tag, take_while1},
+    IResult,
+};
+
+use super::fail;
+
+/// Parses a CommonMark autolink, a URI or email address within `<...>`, returning the URI or
+/// address.
+///
+/// # Example
+/// ```markdown
+/// <https://example.com> <hello@example.com>
+/// ```
+pub fn parse_autolink(input: &str) -> IResult<&str, &str> {
+    let (rest, _) = tag("<")(input)?;
+    let (after, inner) = take_while1(|c: char| c != '>' && c != '<' && !c.is_whitespace())(rest)?;
+    let (after, _) = tag(">")(after)?;
+
+    match is_uri(inner) || is_email(inner) {
+        true => Ok((after, inner)),
+        false => fail(input),
+    }
+}
+
+/// Parses a GFM extended autolink: a bare `http://`, `https://` or `www.` URL, or an email
+/// address. Trailing punctuation, unbalanced closing parentheses and entity references aren't
+/// part of the link.
+pub fn parse_extended_autolink(input: &str) -> IResult<&str, &str> {
+    let link = if let Some(rest) = ["https://", "http://", "www."]
+        .iter()
+        .find_map(|prefix| input.strip_prefix(prefix))
+    {
+        let domain = domain_len(rest);
+
+        if domain == 0 {
+            return fail(input);
+        }
+
+        let end = input.len() - rest.len()
+            + rest
+                .find(|c: char| c.is_whitespace() || c == '<')
+                .unwrap_or(rest.len());
+
+        trim_trailing(&input[..end])
+    } else {
+        email(input)
+    };
+
+    match link.is_empty() {
+        true => fail(input),
+        false => Ok((&input[link.len()..], link)),
+    }
+}
+
+/// Whether a URI has a scheme of 2 to 32 characters followed by `:`.
+fn is_uri(text: &str) -> bool {
+    let Some((scheme, _)) = text.split_once(':') else {
+        return false;
+    };
+
+    (2..=32).contains(&scheme.len())
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '.' | '-'))
+        && !text.chars().any(|c| c.is_control())
+}
+
+fn is_email(text: &str) -> bool {
+    let Some((local, domain)) = text.split_once('@') else {
+        return false;
+    };
+
+    let valid_local = !local.is_empty()
+        && local
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ".!#$%&'*+/=?^_`{|}~-".contains(c));
+
+    let valid_domain = domain.split('.').all(|label| {
+        (1..=63).contains(&label.len())
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    });
+
+    valid_local && valid_domain
+}
+
+/// Returns the length of a valid domain at the start of the text. A domain is segments of
+/// alphanumerics, `_` and `-` separated by periods, with no underscores in the last two segments.
+fn domain_len(text: &str) -> usize {
+    let end = text
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '.' | '_' | '-')))
+        .unwrap_or(text.len());
+    let domain = text[..end].trim_end_matches('.');
+
+    let segments: Vec<&str> = domain.split('.').collect();
+
+    let valid = !domain.is_empty()
+        && segments.iter().all(|segment| !segment.is_empty())
+        && segments
+            .iter()
+            .rev()
+            .take(2)
+            .all(|segment| !segment.contains('_'));
+
+    match valid {
+        true => domain.len(),
+        false => 0,
+    }
+}
+
+/// Removes trailing punctuation, unbalanced closing parentheses and entity references such as
+/// `&amp;` from the end of a link.
+fn trim_trailing(mut link: &str) -> &str {
+    loop {
+        let trimmed = link.trim_end_matches(['?', '!', '.', ',', ':', '*', '_', '~']);
+
+        let trimmed = if trimmed.ends_with(')')
+            && trimmed.matches(')').count() > trimmed.matches('(').count()
+        {
+            &trimmed[..trimmed.len() - 1]
+        } else if let Some(entity) = trimmed.strip_suffix(';').and_then(|t| {
+            let start = t.rfind('&')?;
+            let name = &t[start + 1..];
+            (!name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric())).then_some(start)
+        }) {
+            &trimmed[..entity]
+        } else {
+            trimmed
+        };
+
+        if trimmed.len() == link.len() {
+            return link;
+        }
+
+        link = trimmed;
+    }
+}
+
+/// Returns the email address at the start of the text, or an empty string.
+fn email(text: &str) -> &str {
+    let local = text
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '+')))
+        .unwrap_or(text.len());
+
+    if local == 0 || !text[local..].starts_with('@') {
+        return "";
+    }
+
+    let domain = &text[local + 1..];
+    let end = domain
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_')))
+        .unwrap_or(domain.len());
+    let domain = domain[..end].trim_end_matches('.');
+
+    let valid = domain.contains('.')
+        && !domain.ends_with(['-', '_'])
+        && domain.split('.').all(|label| !label.is_empty());
+
+    match valid {
+        true => &text[..local + 1 + domain.len()],
+        false => "",
+    }
+}
+
+#[cfg(test)]
+mod test_autolink {
+    use super::*;
+
+    #[test]
+    fn test_autolink() {
+        assert_eq!(
+            parse_autolink("<https://example.com/a?b=c> rest"),
+            Ok((" rest", "https://example.com/a?b=c"))
+        );
+        assert_eq!(
+            parse_autolink("<irc://foo.bar:2233/baz>"),
+            Ok(("", "irc://foo.bar:2233/baz"))
+        );
+        assert_eq!(
+            parse_autolink("<foo+special@Bar.baz-bar0.com>"),
+            Ok(("", "foo+special@Bar.baz-bar0.com"))
+        );
+
+        assert!(parse_autolink("<https://exa mple.com>").is_err());
+        assert!(parse_autolink("<m:abc>").is_err());
+        assert!(parse_autolink("<foo.bar.baz>").is_err());
+        assert!(parse_autolink("<div>").is_err());
+    }
+
+    #[test]
+    fn test_extended_autolink() {
+        let link = |input| parse_extended_autolink(input).map(|(_, link)| link);
+
+        assert_eq!(
+            link("www.commonmark.org/help."),
+            Ok("www.commonmark.org/help")
+        );
+        assert_eq!(
+            link("https://en.wikipedia.org/wiki/Rust_(language))."),
+            Ok("https://en.wikipedia.org/wiki/Rust_(language)")
+        );
+        assert_eq!(
+            link("www.google.com/search?q=(business))+ok"),
+            Ok("www.google.com/search?q=(business))+ok")
+        );
+        assert_eq!(link("www.example.com/a&hl;"), Ok("www.example.com/a"));
+        assert_eq!(link("http://example.com<b>"), Ok("http://example.com"));
+        assert_eq!(link("foo@bar.baz."), Ok("foo@bar.baz"));
+        assert_eq!(link("a.b-c_d@a.b"), Ok("a.b-c_d@a.b"));
+
+        assert!(link("www.exa_mple.com").is_err());
+        assert!(link("https://").is_err());
+        assert!(link("a.b-c_d@a.b_").is_err());
+        assert!(link("foo@bar").is_err());
+        assert!(link("plain text").is_err());
+    }
+}
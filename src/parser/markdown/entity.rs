@@ -0,0 +1,82 @@
+mod table;
+
+use table::ENTITIES;
+
+/// The length of the longest reference name along with its `;`,
+/// `CounterClockwiseContourIntegral;`.
+const MAX_LEN: usize = 32;
+
+/// Decodes the entity or numeric character reference at the start of the input, such as `&copy;`,
+/// `&#169;` or `&#xA9;`, returning its characters and length. Numeric references to invalid code
+/// points decode to U+FFFD. Only the first 32 bytes are searched for the closing `;`, which is
+/// enough for the longest entity name.
+///
+/// # Example
+/// ```
+/// use mdx_rs::parser::markdown::entity::decode;
+///
+/// assert_eq!(decode("&copy; 2024"), Some(("\u{a9}".into(), 6)));
+/// ```
+pub fn decode(input: &str) -> Option<(String, usize)> {
+    let rest = input.strip_prefix('&')?;
+    let end = rest.bytes().take(MAX_LEN).position(|b| b == b';')?;
+    let name = &rest[..end];
+    let len = end + 2;
+
+    if let Some(number) = name.strip_prefix('#') {
+        let (digits, radix, max) = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => (hex, 16, 6),
+            None => (number, 10, 7),
+        };
+
+        if digits.is_empty() || digits.len() > max || !digits.chars().all(|c| c.is_digit(radix)) {
+            return None;
+        }
+
+        let c = u32::from_str_radix(digits, radix)
+            .ok()
+            .filter(|&code| code != 0)
+            .and_then(char::from_u32)
+            .unwrap_or(char::REPLACEMENT_CHARACTER);
+
+        return Some((c.to_string(), len));
+    }
+
+    let index = ENTITIES
+        .binary_search_by(|(entity, _)| (*entity).cmp(name))
+        .ok()?;
+
+    Some((ENTITIES[index].1.to_string(), len))
+}
+
+#[cfg(test)]
+mod test_entity {
+    use super::*;
+
+    #[test]
+    fn test_named() {
+        assert_eq!(decode("&amp;"), Some(("&".to_string(), 5)));
+        assert_eq!(decode("&nbsp;x"), Some(("\u{a0}".to_string(), 6)));
+        assert_eq!(decode("&ngE;"), Some(("\u{2267}\u{338}".to_string(), 5)));
+        assert_eq!(decode("&ClockwiseContourIntegral;").unwrap().1, 26);
+
+        assert_eq!(decode("&amp"), None);
+        assert_eq!(decode("&madeup;"), None);
+        assert_eq!(decode("& amp;"), None);
+        assert_eq!(decode("&CounterClockwiseContourIntegral;").unwrap().1, 33);
+        assert_eq!(decode(&format!("&{};", "a".repeat(40))), None);
+    }
+
+    #[test]
+    fn test_numeric() {
+        assert_eq!(decode("&#35;"), Some(("#".to_string(), 5)));
+        assert_eq!(decode("&#x27;"), Some(("'".to_string(), 6)));
+        assert_eq!(decode("&#X22;"), Some(("\"".to_string(), 6)));
+        assert_eq!(decode("&#0;"), Some(("\u{fffd}".to_string(), 4)));
+        assert_eq!(decode("&#xD800;"), Some(("\u{fffd}".to_string(), 8)));
+
+        assert_eq!(decode("&#;"), None);
+        assert_eq!(decode("&#12345678;"), None);
+        assert_eq!(decode("&#xG;"), None);
+    }
+}
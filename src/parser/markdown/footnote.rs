@@ -0,0 +1,225 @@
+use super::reference::normalize_label;
+use super::{Block, TextBlockItem};
+use std::collections::HashMap;
+
+/// The footnotes that couldn't be linked by [`resolve`].
+#[derive(Debug, Default, PartialEq)]
+pub struct Unresolved<'a> {
+    /// The names of references without a definition.
+    pub undefined: Vec<&'a str>,
+    /// The names of footnotes that are never referenced.
+    pub unused: Vec<&'a str>,
+    /// The names of the definitions of footnotes that are already defined, which are ignored.
+    pub duplicate: Vec<&'a str>,
+}
+
+impl<'a> Unresolved<'a> {
+    pub fn is_empty(&self) -> bool {
+        self.undefined.is_empty() && self.unused.is_empty() && self.duplicate.is_empty()
+    }
+}
+
+/// Links footnote references to their definitions, numbering the footnotes in the order they are
+/// first referenced and counting the references to each. Names are matched like reference
+/// labels, when a footnote is defined more than once the first definition is used and the others
+/// are reported.
+///
+/// # Example
+/// ```
+/// use mdx_rs::parser::markdown::{footnote, Block};
+///
+/// let (_, mut blocks) = Block::parse("Text[^b] and more[^a].\n\n[^a]: A.\n[^b]: B.\n").unwrap();
+/// let unresolved = footnote::resolve(&mut blocks);
+///
+/// assert!(unresolved.is_empty());
+/// ```
+pub fn resolve<'a>(blocks: &mut [Block<'a>]) -> Unresolved<'a> {
+    let mut resolver = Resolver::default();
+    resolver.define(blocks);
+    resolver.blocks(blocks);
+    resolver.number(blocks);
+
+    resolver.unresolved
+}
+
+#[derive(Default)]
+struct Resolver<'a> {
+    /// The number and reference count of each defined footnote, numbered once referenced.
+    footnotes: HashMap<String, (Option<usize>, usize)>,
+    count: usize,
+    unresolved: Unresolved<'a>,
+}
+
+impl<'a> Resolver<'a> {
+    fn define(&mut self, blocks: &[Block<'a>]) {
+        for block in blocks {
+            let Block::Footnote(footnote) = block else {
+                continue;
+            };
+
+            let label = normalize_label(footnote.name);
+
+            match self.footnotes.contains_key(&label) {
+                true => self.unresolved.duplicate.push(footnote.name),
+                false => {
+                    self.footnotes.insert(label, (None, 0));
+                }
+            }
+        }
+    }
+
+    fn blocks(&mut self, blocks: &mut [Block<'a>]) {
+        for block in blocks {
+            match block {
+                Block::TextBlock(text_block) => self.items(&mut text_block.contents),
+                Block::Footnote(footnote) => self.blocks(&mut footnote.blocks),
+                Block::Admonition(admonition) => self.blocks(&mut admonition.blocks),
+                Block::DefinitionList(list) => {
+                    for item in &mut list.items {
+                        item.terms.iter_mut().for_each(|term| self.items(term));
+                        item.definitions
+                            .iter_mut()
+                            .for_each(|definition| self.blocks(definition));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn items(&mut self, items: &mut [TextBlockItem<'a>]) {
+        for item in items {
+            match item {
+                TextBlockItem::FootnoteRef(reference) => {
+                    let Some((number, count)) =
+                        self.footnotes.get_mut(&normalize_label(reference.name))
+                    else {
+                        self.unresolved.undefined.push(reference.name);
+                        continue;
+                    };
+
+                    if number.is_none() {
+                        self.count += 1;
+                        *number = Some(self.count);
+                    }
+
+                    *count += 1;
+                    reference.number = *number;
+                    reference.occurrence = *count;
+                }
+                TextBlockItem::Emphasis(items)
+                | TextBlockItem::Strong(items)
+                | TextBlockItem::Strikethrough(items)
+                | TextBlockItem::Highlight(items)
+                | TextBlockItem::Superscript(items)
+                | TextBlockItem::Subscript(items) => self.items(items),
+                _ => {}
+            }
+        }
+    }
+
+    /// Attaches the numbers and reference counts to the first definition of each footnote.
+    fn number(&mut self, blocks: &mut [Block<'a>]) {
+        for block in blocks {
+            let Block::Footnote(footnote) = block else {
+                continue;
+            };
+
+            let Some((number, count)) = self.footnotes.remove(&normalize_label(footnote.name))
+            else {
+                continue;
+            };
+
+            match number {
+                Some(_) => {
+                    footnote.number = number;
+                    footnote.references = count;
+                }
+                None => self.unresolved.unused.push(footnote.name),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_footnote {
+    use super::*;
+    use crate::parser::markdown::{Footnote, FootnoteRef};
+    use indoc::indoc;
+
+    fn references<'a>(items: &'a [TextBlockItem<'a>]) -> Vec<&'a FootnoteRef<'a>> {
+        items
+            .iter()
+            .filter_map(|item| match item {
+                TextBlockItem::FootnoteRef(reference) => Some(reference),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve() {
+        let input = indoc! {"
+            First[^b], second[^A] and *again[^b]*, then[^missing].
+
+            [^a]: Footnote a, which refers to[^c].
+            [^b]: Footnote b.
+            [^c]: Footnote c.
+            [^unused]: Never referenced.
+            [^B]: A duplicate.
+        "};
+
+        let (rest, mut blocks) = Block::parse(input).unwrap();
+        assert_eq!(rest, "");
+
+        let unresolved = resolve(&mut blocks);
+
+        assert_eq!(
+            unresolved,
+            Unresolved {
+                undefined: vec!["missing"],
+                unused: vec!["unused"],
+                duplicate: vec!["B"],
+            }
+        );
+
+        let Block::TextBlock(text_block) = &blocks[0] else {
+            panic!("expected a text block");
+        };
+
+        let refs = references(&text_block.contents);
+        assert_eq!((refs[0].number, refs[0].occurrence), (Some(1), 1));
+        assert_eq!((refs[1].number, refs[1].occurrence), (Some(2), 1));
+        assert_eq!((refs[2].number, refs[2].occurrence), (None, 0));
+
+        let TextBlockItem::Emphasis(items) = &text_block.contents[5] else {
+            panic!("expected emphasis");
+        };
+
+        assert_eq!(references(items)[0].occurrence, 2);
+
+        let footnotes: Vec<(&str, Option<usize>, usize)> = blocks
+            .iter()
+            .filter_map(|block| match block {
+                Block::Footnote(Footnote {
+                    name,
+                    number,
+                    references,
+                    ..
+                }) => Some((*name, *number, *references)),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            footnotes,
+            vec![
+                ("a", Some(2), 1),
+                ("b", Some(1), 2),
+                ("c", Some(3), 1),
+                ("unused", None, 0),
+                ("B", None, 0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,366 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_till, take_until, take_while, take_while1},
+    character::complete::{char, multispace0, multispace1, satisfy},
+    combinator::{opt, recognize},
+    multi::many0,
+    sequence::{delimited, pair, preceded, tuple},
+    IResult,
+};
+
+use super::{fail, parse_indentation};
+
+/// Tags whose contents are kept as they are until the closing tag, even across blank lines.
+const RAW_TAGS: [&str; 4] = ["pre", "script", "style", "textarea"];
+
+/// Tags that start an HTML block ending at a blank line, which can interrupt a paragraph.
+const BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "base",
+    "basefont",
+    "blockquote",
+    "body",
+    "caption",
+    "center",
+    "col",
+    "colgroup",
+    "dd",
+    "details",
+    "dialog",
+    "dir",
+    "div",
+    "dl",
+    "dt",
+    "fieldset",
+    "figcaption",
+    "figure",
+    "footer",
+    "form",
+    "frame",
+    "frameset",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "head",
+    "header",
+    "hr",
+    "html",
+    "iframe",
+    "legend",
+    "li",
+    "link",
+    "main",
+    "menu",
+    "menuitem",
+    "nav",
+    "noframes",
+    "ol",
+    "optgroup",
+    "option",
+    "p",
+    "param",
+    "search",
+    "section",
+    "summary",
+    "table",
+    "tbody",
+    "td",
+    "tfoot",
+    "th",
+    "thead",
+    "title",
+    "tr",
+    "track",
+    "ul",
+];
+
+/// How an HTML block ends, which depends on how it starts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum End {
+    /// The line containing a closing tag for one of the [`RAW_TAGS`].
+    RawTag,
+    /// The line containing the text, such as `-->` for a comment.
+    Contains(&'static str),
+    /// The line before a blank line.
+    BlankLine,
+}
+
+impl End {
+    fn matches(self, line: &str) -> bool {
+        match self {
+            End::RawTag => {
+                let line = line.to_ascii_lowercase();
+                RAW_TAGS
+                    .iter()
+                    .any(|name| line.contains(&format!("</{name}>")))
+            }
+            End::Contains(text) => line.contains(text),
+            End::BlankLine => line.trim().is_empty(),
+        }
+    }
+}
+
+/// Whether the text starts with the tag name, ignoring case, followed by the end of the name.
+fn starts_with_name(text: &str, name: &str, ends: &[&str]) -> bool {
+    let Some(prefix) = text.get(..name.len()) else {
+        return false;
+    };
+
+    let after = &text[name.len()..];
+
+    prefix.eq_ignore_ascii_case(name)
+        && (after.trim_end_matches(['\r', '\n']).is_empty()
+            || ends.iter().any(|end| after.starts_with(end)))
+}
+
+/// Returns how the HTML block starting at the line ends, or `None` if the line doesn't start one.
+/// A block of any tag that stands alone on its line can't interrupt a paragraph.
+fn start(line: &str, interrupting: bool) -> Option<End> {
+    let (rest, _) = parse_indentation(line).ok()?;
+    let tag = rest.strip_prefix('<')?;
+
+    if RAW_TAGS
+        .iter()
+        .any(|name| starts_with_name(tag, name, &[" ", "\t", ">"]))
+    {
+        return Some(End::RawTag);
+    }
+
+    let markers = [("!--", "-->"), ("?", "?>"), ("![CDATA[", "]]>")];
+
+    if let Some((_, end)) = markers.iter().find(|(start, _)| tag.starts_with(start)) {
+        return Some(End::Contains(end));
+    }
+
+    if tag.starts_with('!') && tag[1..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Some(End::Contains(">"));
+    }
+
+    let name = tag.strip_prefix('/').unwrap_or(tag);
+
+    if BLOCK_TAGS
+        .iter()
+        .any(|block| starts_with_name(name, block, &[" ", "\t", ">", "/>"]))
+    {
+        return Some(End::BlankLine);
+    }
+
+    if interrupting {
+        return None;
+    }
+
+    let (after, _) = alt((open_tag, closing_tag))(rest).ok()?;
+    let (_, name) = tag_name(name).ok()?;
+    let raw = RAW_TAGS.iter().any(|raw| raw.eq_ignore_ascii_case(name));
+
+    match after.trim().is_empty() && !raw {
+        true => Some(End::BlankLine),
+        false => None,
+    }
+}
+
+/// Whether the line starts an HTML block that can interrupt a paragraph.
+pub fn interrupts_paragraph(line: &str) -> bool {
+    start(line, true).is_some()
+}
+
+/// Parses an HTML block, returning its lines without the final line ending. Depending on how it
+/// starts, the block ends at a blank line or at the line containing its end marker, such as
+/// `-->` for a comment.
+///
+/// # Example
+/// ```markdown
+/// <details>
+/// <summary>More</summary>
+/// </details>
+/// ```
+pub fn parse_block(input: &str) -> IResult<&str, &str> {
+    let first = input.split_inclusive('\n').next().unwrap_or(input);
+
+    let Some(end) = start(first, false) else {
+        return fail(input);
+    };
+
+    let mut len = 0;
+
+    for line in input.split_inclusive('\n') {
+        if end == End::BlankLine && end.matches(line) {
+            break;
+        }
+
+        len += line.len();
+
+        if end != End::BlankLine && end.matches(line) {
+            break;
+        }
+    }
+
+    Ok((&input[len..], input[..len].trim_end_matches(['\r', '\n'])))
+}
+
+/// Parses inline raw HTML: an opening or closing tag, a comment, a processing instruction, a
+/// declaration or a CDATA section.
+pub fn parse_inline(input: &str) -> IResult<&str, &str> {
+    alt((
+        open_tag,
+        closing_tag,
+        comment,
+        processing_instruction,
+        cdata,
+        declaration,
+    ))(input)
+}
+
+fn tag_name(input: &str) -> IResult<&str, &str> {
+    recognize(pair(
+        satisfy(|c| c.is_ascii_alphabetic()),
+        take_while(|c: char| c.is_ascii_alphanumeric() || c == '-'),
+    ))(input)
+}
+
+fn attribute(input: &str) -> IResult<&str, &str> {
+    let name = recognize(pair(
+        satisfy(|c| c.is_ascii_alphabetic() || c == '_' || c == ':'),
+        take_while(|c: char| c.is_ascii_alphanumeric() || "_.:-".contains(c)),
+    ));
+
+    let value = alt((
+        delimited(char('"'), take_till(|c| c == '"'), char('"')),
+        delimited(char('\''), take_till(|c| c == '\''), char('\'')),
+        take_while1(|c: char| !c.is_whitespace() && !"\"'=<>`".contains(c)),
+    ));
+
+    recognize(tuple((
+        multispace1,
+        name,
+        opt(tuple((multispace0, char('='), multispace0, value))),
+    )))(input)
+}
+
+fn open_tag(input: &str) -> IResult<&str, &str> {
+    recognize(tuple((
+        char('<'),
+        tag_name,
+        many0(attribute),
+        multispace0,
+        opt(char('/')),
+        char('>'),
+    )))(input)
+}
+
+fn closing_tag(input: &str) -> IResult<&str, &str> {
+    recognize(tuple((tag("</"), tag_name, multispace0, char('>'))))(input)
+}
+
+fn comment(input: &str) -> IResult<&str, &str> {
+    alt((
+        tag("<!-->"),
+        tag("<!--->"),
+        recognize(tuple((tag("<!--"), take_until("-->"), tag("-->")))),
+    ))(input)
+}
+
+fn processing_instruction(input: &str) -> IResult<&str, &str> {
+    recognize(tuple((tag("<?"), take_until("?>"), tag("?>"))))(input)
+}
+
+fn cdata(input: &str) -> IResult<&str, &str> {
+    recognize(tuple((tag("<![CDATA["), take_until("]]>"), tag("]]>"))))(input)
+}
+
+fn declaration(input: &str) -> IResult<&str, &str> {
+    recognize(tuple((
+        tag("<!"),
+        preceded(
+            satisfy(|c| c.is_ascii_alphabetic()),
+            take_till(|c| c == '>'),
+        ),
+        char('>'),
+    )))(input)
+}
+
+#[cfg(test)]
+mod test_html {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_block() {
+        let input = indoc! {"
+            <details>
+            <summary>More</summary>
+
+            text
+        "};
+
+        assert_eq!(
+            parse_block(input),
+            Ok(("\ntext\n", "<details>\n<summary>More</summary>"))
+        );
+
+        let input = indoc! {"
+            <pre>
+            code
+
+            more code
+            </pre> after
+            text
+        "};
+
+        assert_eq!(
+            parse_block(input),
+            Ok(("text\n", "<pre>\ncode\n\nmore code\n</pre> after"))
+        );
+
+        assert_eq!(
+            parse_block("<!-- a\n\ncomment --> x\ny"),
+            Ok(("y", "<!-- a\n\ncomment --> x"))
+        );
+        assert_eq!(
+            parse_block("<!DOCTYPE html>\n"),
+            Ok(("", "<!DOCTYPE html>"))
+        );
+        assert_eq!(
+            parse_block("<custom-tag a=\"1\">\n"),
+            Ok(("", "<custom-tag a=\"1\">"))
+        );
+        assert!(parse_block("<span>text</span>\n").is_err());
+        assert!(parse_block("    <div>\n").is_err());
+        assert!(parse_block("<divider> text\n").is_err());
+    }
+
+    #[test]
+    fn test_interrupts_paragraph() {
+        assert!(interrupts_paragraph("<div class=\"note\">\n"));
+        assert!(interrupts_paragraph("</DIV>\n"));
+        assert!(interrupts_paragraph("<!-- comment -->\n"));
+        assert!(!interrupts_paragraph("<custom-tag>\n"));
+    }
+
+    #[test]
+    fn test_parse_inline() {
+        assert_eq!(
+            parse_inline("<a href='x' data-id=1 hidden/> text"),
+            Ok((" text", "<a href='x' data-id=1 hidden/>"))
+        );
+        assert_eq!(parse_inline("</em >"), Ok(("", "</em >")));
+        assert_eq!(
+            parse_inline("<!-- a -- b -->c"),
+            Ok(("c", "<!-- a -- b -->"))
+        );
+        assert_eq!(
+            parse_inline("<?php echo 1; ?>"),
+            Ok(("", "<?php echo 1; ?>"))
+        );
+        assert_eq!(parse_inline("<![CDATA[<x>]]>"), Ok(("", "<![CDATA[<x>]]>")));
+        assert_eq!(parse_inline("<!ELEMENT x>"), Ok(("", "<!ELEMENT x>")));
+        assert!(parse_inline("<a href=\"x>").is_err());
+        assert!(parse_inline("<1a>").is_err());
+        assert!(parse_inline("< a>").is_err());
+    }
+}
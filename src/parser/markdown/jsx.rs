@@ -0,0 +1,179 @@
+use nom::{
+    branch::alt,
+    bytes::complete::{take_till, take_while},
+    character::complete::{char, line_ending, multispace0, multispace1, satisfy, space0},
+    combinator::{eof, opt, recognize},
+    multi::{many0, many1},
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    IResult,
+};
+
+use super::{fail, parse_indentation};
+
+/// Parses a JavaScript expression within braces, returning what's between them. Braces within
+/// string literals don't count towards the nesting.
+///
+/// # Example
+/// ```markdown
+/// {props.count + 1} or {/* a comment */}
+/// ```
+pub fn parse_expression(input: &str) -> IResult<&str, &str> {
+    if !input.starts_with('{') {
+        return fail(input);
+    }
+
+    let mut depth = 0;
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (i, c) in input.char_indices() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (None, '{') => depth += 1,
+            (None, '}') => {
+                depth -= 1;
+
+                if depth == 0 {
+                    return Ok((&input[i + 1..], &input[1..i]));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fail(input)
+}
+
+fn identifier(input: &str) -> IResult<&str, &str> {
+    recognize(pair(
+        satisfy(|c| c.is_alphabetic() || c == '$' || c == '_'),
+        take_while(|c: char| c.is_alphanumeric() || matches!(c, '$' | '_' | '-')),
+    ))(input)
+}
+
+/// A tag name such as `div`, a member expression such as `Tabs.Item` or a namespaced name such
+/// as `svg:rect`.
+fn name(input: &str) -> IResult<&str, &str> {
+    recognize(pair(
+        identifier,
+        alt((
+            recognize(many1(preceded(char('.'), identifier))),
+            recognize(opt(preceded(char(':'), identifier))),
+        )),
+    ))(input)
+}
+
+fn attribute(input: &str) -> IResult<&str, &str> {
+    let value = alt((
+        delimited(char('"'), take_till(|c| c == '"'), char('"')),
+        delimited(char('\''), take_till(|c| c == '\''), char('\'')),
+        parse_expression,
+    ));
+
+    alt((
+        recognize(parse_expression),
+        recognize(tuple((
+            identifier,
+            opt(preceded(char(':'), identifier)),
+            opt(tuple((multispace0, char('='), multispace0, value))),
+        ))),
+    ))(input)
+}
+
+/// Parses a JSX opening, closing or self-closing tag, including fragments such as `<>`.
+///
+/// # Example
+/// ```markdown
+/// <Callout type="info" {...props}> </Callout> <Chart data={[1, 2]} /> <></>
+/// ```
+pub fn parse_tag(input: &str) -> IResult<&str, &str> {
+    let closing = tuple((char('/'), multispace0, opt(name), multispace0));
+
+    let opening = opt(tuple((
+        name,
+        many0(preceded(multispace1, attribute)),
+        multispace0,
+        opt(char('/')),
+        multispace0,
+    )));
+
+    recognize(tuple((
+        char('<'),
+        multispace0,
+        alt((recognize(closing), recognize(opening))),
+        char('>'),
+    )))(input)
+}
+
+/// Parses a block of JSX, one or more tags and expressions starting a line with nothing else
+/// after them on their last line. The content between tags is parsed as markdown.
+pub fn parse_block(input: &str) -> IResult<&str, &str> {
+    let item = |i| alt((parse_tag, recognize(parse_expression)))(i);
+
+    let (rest, _) = tuple((
+        parse_indentation,
+        parse_tag,
+        many0(preceded(space0, item)),
+        space0,
+    ))(input)?;
+    let (rest, _) = alt((line_ending, eof))(rest)?;
+
+    let len = input.len() - rest.len();
+    Ok((rest, input[..len].trim_end_matches(['\r', '\n'])))
+}
+
+/// Parses an expression that makes up a whole line, such as `{/* a comment */}`.
+pub fn parse_expression_block(input: &str) -> IResult<&str, &str> {
+    let (rest, expression) = preceded(
+        parse_indentation,
+        terminated(parse_expression, pair(space0, alt((line_ending, eof)))),
+    )(input)?;
+
+    Ok((rest, expression))
+}
+
+#[cfg(test)]
+mod test_jsx {
+    use super::*;
+
+    #[test]
+    fn test_parse_expression() {
+        assert_eq!(parse_expression("{a + b} c"), Ok((" c", "a + b")));
+        assert_eq!(parse_expression("{{a: '}'}} c"), Ok((" c", "{a: '}'}")));
+        assert_eq!(parse_expression("{`${x}`}"), Ok(("", "`${x}`")));
+        assert!(parse_expression("{unclosed").is_err());
+    }
+
+    #[test]
+    fn test_parse_tag() {
+        let tag = "<Chart\n  data={[1, 2]}\n  title=\"A > B\"\n  {...props}\n  hidden\n/>";
+
+        assert_eq!(parse_tag(tag), Ok(("", tag)));
+        assert_eq!(parse_tag("<Tabs.Item>x"), Ok(("x", "<Tabs.Item>")));
+        assert_eq!(parse_tag("<svg:rect />"), Ok(("", "<svg:rect />")));
+        assert_eq!(parse_tag("</ Tabs.Item >"), Ok(("", "</ Tabs.Item >")));
+        assert_eq!(parse_tag("<></>"), Ok(("</>", "<>")));
+        assert!(parse_tag("<!-- comment -->").is_err());
+        assert!(parse_tag("<1>").is_err());
+    }
+
+    #[test]
+    fn test_parse_block() {
+        assert_eq!(
+            parse_block("<Callout type=\"note\"> {title}\nText"),
+            Ok(("Text", "<Callout type=\"note\"> {title}"))
+        );
+        assert_eq!(parse_block("</Callout>"), Ok(("", "</Callout>")));
+        assert!(parse_block("<b>bold</b> text\n").is_err());
+        assert!(parse_block("{expression}\n").is_err());
+
+        assert_eq!(
+            parse_expression_block("{/* comment */}  \nText"),
+            Ok(("Text", "/* comment */"))
+        );
+    }
+}
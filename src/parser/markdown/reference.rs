@@ -0,0 +1,412 @@
+use super::{
+    parse_inline, Block, Definition, Image, Link, Options, Reference, Text, TextBlock,
+    TextBlockItem,
+};
+use std::collections::HashMap;
+
+/// Normalizes a reference label for matching: case is ignored and runs of whitespace are treated
+/// as a single space.
+pub fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Attaches the destination and title of their definitions to reference links and images,
+/// returning the labels of the full references, `[text][label]`, without a definition. Shortcut
+/// and collapsed references without a definition are turned back into text. When a label is
+/// defined more than once the first definition is used.
+///
+/// # Example
+/// ```
+/// use mdx_rs::parser::markdown::{reference, Block};
+///
+/// let (_, mut blocks) = Block::parse("See [the docs][Docs].\n\n[docs]: https://docs.rs\n").unwrap();
+/// let undefined = reference::resolve(&mut blocks);
+///
+/// assert!(undefined.is_empty());
+/// ```
+pub fn resolve<'a>(blocks: &mut [Block<'a>]) -> Vec<&'a str> {
+    resolve_with(blocks, &Options::default())
+}
+
+/// Resolves references like [`resolve`], parsing the text of references that turn back into text
+/// with the options the blocks were parsed with.
+pub fn resolve_with<'a>(blocks: &mut [Block<'a>], options: &Options) -> Vec<&'a str> {
+    let mut resolver = Resolver {
+        definitions: HashMap::new(),
+        undefined: Vec::new(),
+        options,
+    };
+
+    resolver.define(blocks);
+    resolver.blocks(blocks);
+
+    resolver.undefined
+}
+
+struct Resolver<'a, 'o> {
+    definitions: HashMap<String, (&'a str, Option<&'a str>)>,
+    undefined: Vec<&'a str>,
+    options: &'o Options,
+}
+
+impl<'a, 'o> Resolver<'a, 'o> {
+    /// Collects the definitions, including those within containers such as footnotes.
+    fn define(&mut self, blocks: &[Block<'a>]) {
+        for block in blocks {
+            match block {
+                Block::Definition(Definition { label, url, title }) => {
+                    self.definitions
+                        .entry(normalize_label(label))
+                        .or_insert((url, *title));
+                }
+                Block::Footnote(footnote) => self.define(&footnote.blocks),
+                Block::Admonition(admonition) => self.define(&admonition.blocks),
+                Block::DefinitionList(list) => {
+                    for item in &list.items {
+                        item.definitions
+                            .iter()
+                            .for_each(|definition| self.define(definition));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn blocks(&mut self, blocks: &mut [Block<'a>]) {
+        for block in blocks {
+            let literal = match block {
+                Block::Link(link) => {
+                    (!self.link(link)).then(|| self.literal("[", link.text, link.reference))
+                }
+                Block::Image(image) => {
+                    (!self.image(image)).then(|| self.literal("![", image.alt, image.reference))
+                }
+                Block::TextBlock(text_block) => {
+                    self.items(&mut text_block.contents);
+                    None
+                }
+                Block::Footnote(footnote) => {
+                    self.blocks(&mut footnote.blocks);
+                    None
+                }
+                Block::Admonition(admonition) => {
+                    self.blocks(&mut admonition.blocks);
+                    None
+                }
+                Block::DefinitionList(list) => {
+                    for item in &mut list.items {
+                        item.terms.iter_mut().for_each(|term| self.items(term));
+                        item.definitions
+                            .iter_mut()
+                            .for_each(|definition| self.blocks(definition));
+                    }
+
+                    None
+                }
+                _ => None,
+            };
+
+            if let Some(contents) = literal {
+                *block = Block::TextBlock(TextBlock { contents });
+            }
+        }
+    }
+
+    /// Attaches the definition of a reference, returning false for a shortcut or collapsed
+    /// reference without one.
+    fn resolve(
+        &mut self,
+        reference: Option<Reference<'a>>,
+        label: Option<&'a str>,
+        url: &mut &'a str,
+        title: &mut Option<&'a str>,
+    ) -> bool {
+        let (Some(reference), Some(label)) = (reference, label) else {
+            return true;
+        };
+
+        match self.definitions.get(&normalize_label(label)) {
+            Some((destination, definition_title)) => {
+                *url = destination;
+                *title = *definition_title;
+            }
+            None if matches!(reference, Reference::Full(_)) => self.undefined.push(label),
+            None => return false,
+        }
+
+        true
+    }
+
+    fn link(&mut self, link: &mut Link<'a>) -> bool {
+        self.resolve(link.reference, link.label(), &mut link.url, &mut link.title)
+    }
+
+    fn image(&mut self, image: &mut Image<'a>) -> bool {
+        self.resolve(
+            image.reference,
+            image.label(),
+            &mut image.source,
+            &mut image.title,
+        )
+    }
+
+    /// Returns the source of an unmatched reference as text, the text within the brackets is
+    /// parsed as inline content.
+    fn literal(
+        &mut self,
+        open: &'static str,
+        text: &'a str,
+        reference: Option<Reference<'a>>,
+    ) -> Vec<TextBlockItem<'a>> {
+        let close = match reference {
+            Some(Reference::Collapsed) => "][]",
+            _ => "]",
+        };
+
+        let mut items = vec![TextBlockItem::Text(Text(open))];
+
+        if let Ok((_, mut inner)) = parse_inline(text, self.options) {
+            self.items(&mut inner);
+            items.extend(inner);
+        }
+
+        items.push(TextBlockItem::Text(Text(close)));
+        items
+    }
+
+    fn items(&mut self, items: &mut Vec<TextBlockItem<'a>>) {
+        for mut item in std::mem::take(items) {
+            let literal = match &mut item {
+                TextBlockItem::Link(link) => {
+                    (!self.link(link)).then(|| self.literal("[", link.text, link.reference))
+                }
+                TextBlockItem::Image(image) => {
+                    (!self.image(image)).then(|| self.literal("![", image.alt, image.reference))
+                }
+                TextBlockItem::Emphasis(inner)
+                | TextBlockItem::Strong(inner)
+                | TextBlockItem::Strikethrough(inner)
+                | TextBlockItem::Highlight(inner)
+                | TextBlockItem::Superscript(inner)
+                | TextBlockItem::Subscript(inner) => {
+                    self.items(inner);
+                    None
+                }
+                _ => None,
+            };
+
+            match literal {
+                Some(literal) => items.extend(literal),
+                None => items.push(item),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_reference {
+    use super::*;
+    use indoc::indoc;
+
+    fn links<'a>(blocks: &'a [Block<'a>]) -> Vec<&'a Link<'a>> {
+        blocks
+            .iter()
+            .flat_map(|block| match block {
+                Block::TextBlock(text_block) => text_block.contents.iter().collect(),
+                _ => Vec::new(),
+            })
+            .filter_map(|item| match item {
+                TextBlockItem::Link(link) => Some(link),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve() {
+        let input = indoc! {r#"
+            Read [the guide][Guide], the [FAQ][] or [Rust  Book].
+
+            ![logo][]
+
+            [guide]: https://example.com/guide "The Guide"
+            [faq]:
+              <https://example.com/faq>
+              'Questions'
+            [rust book]: https://doc.rust-lang.org/book/
+            [GUIDE]: https://example.com/ignored
+            [logo]: /logo.png
+        "#};
+
+        let (rest, mut blocks) = Block::parse(input).unwrap();
+        assert_eq!(rest, "");
+
+        let undefined = resolve(&mut blocks);
+        assert!(undefined.is_empty());
+
+        let links = links(&blocks);
+
+        assert_eq!(
+            links[0],
+            &Link {
+                text: "the guide",
+                url: "https://example.com/guide",
+                title: Some("The Guide"),
+                reference: Some(Reference::Full("Guide")),
+                autolink: false,
+            }
+        );
+
+        assert_eq!(links[1].url, "https://example.com/faq");
+        assert_eq!(links[1].title, Some("Questions"));
+        assert_eq!(links[1].reference, Some(Reference::Collapsed));
+
+        assert_eq!(links[2].url, "https://doc.rust-lang.org/book/");
+        assert_eq!(links[2].title, None);
+        assert_eq!(links[2].reference, Some(Reference::Shortcut));
+
+        let image = blocks.iter().find_map(|block| match block {
+            Block::Image(image) => Some(image),
+            _ => None,
+        });
+
+        assert_eq!(
+            image,
+            Some(&Image {
+                alt: "logo",
+                source: "/logo.png",
+                title: None,
+                reference: Some(Reference::Collapsed),
+            })
+        );
+    }
+
+    #[test]
+    fn test_undefined() {
+        let input = "A [missing][link] and [inline](https://example.com), *[nested]*.\n";
+
+        let (_, mut blocks) = Block::parse(input).unwrap();
+        assert_eq!(resolve(&mut blocks), vec!["link"]);
+        assert_eq!(links(&blocks)[0].url, "");
+
+        let Block::TextBlock(text_block) = &blocks[0] else {
+            panic!("expected a text block");
+        };
+
+        assert_eq!(
+            text_block.contents[5],
+            TextBlockItem::Emphasis(vec![
+                TextBlockItem::Text(Text("[")),
+                TextBlockItem::Text(Text("nested")),
+                TextBlockItem::Text(Text("]")),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_unmatched_shortcut() {
+        let input = "See [1], [*a*][] and ![b].\n\n[x]\n\n[1]: /one\n";
+
+        let (_, mut blocks) = Block::parse(input).unwrap();
+        assert!(resolve(&mut blocks).is_empty());
+
+        let text = |text| TextBlockItem::Text(Text(text));
+
+        assert_eq!(
+            blocks[..2],
+            [
+                Block::TextBlock(TextBlock {
+                    contents: vec![
+                        text("See "),
+                        TextBlockItem::Link(Link {
+                            text: "1",
+                            url: "/one",
+                            title: None,
+                            reference: Some(Reference::Shortcut),
+                            autolink: false,
+                        }),
+                        text(", "),
+                        text("["),
+                        TextBlockItem::Emphasis(vec![text("a")]),
+                        text("][]"),
+                        text(" and "),
+                        text("!["),
+                        text("b"),
+                        text("]"),
+                        text("."),
+                    ],
+                }),
+                Block::TextBlock(TextBlock {
+                    contents: vec![text("["), text("x"), text("]")],
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_containers() {
+        let input = indoc! {"
+            :::note
+            See [docs].
+
+            [guide]: /guide
+            :::
+
+            [^1]: Read the [guide][].
+
+            Term [docs]
+            : A [guide].
+
+            [docs]: https://x
+        "};
+
+        let options = Options {
+            admonitions: true,
+            definition_lists: true,
+            ..Options::default()
+        };
+
+        let (_, mut blocks) = Block::parse_with(input, &options).unwrap();
+        assert!(resolve_with(&mut blocks, &options).is_empty());
+
+        let mut urls = Vec::new();
+        let mut visit = vec![&blocks[..]];
+
+        while let Some(blocks) = visit.pop() {
+            urls.extend(links(blocks).iter().map(|link| link.url));
+
+            for block in blocks {
+                match block {
+                    Block::Footnote(footnote) => visit.push(&footnote.blocks),
+                    Block::Admonition(admonition) => visit.push(&admonition.blocks),
+                    Block::DefinitionList(list) => {
+                        for item in &list.items {
+                            for term in &item.terms {
+                                urls.extend(term.iter().filter_map(|item| match item {
+                                    TextBlockItem::Link(link) => Some(link.url),
+                                    _ => None,
+                                }));
+                            }
+
+                            visit.extend(item.definitions.iter().map(Vec::as_slice));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        urls.sort();
+        assert_eq!(urls, vec!["/guide", "/guide", "https://x", "https://x"]);
+    }
+
+    #[test]
+    fn test_normalize_label() {
+        assert_eq!(normalize_label("  Foo\n  BAR "), "foo bar");
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+
+/// Converts text into a GitHub-style anchor slug: lowercased, with punctuation removed and spaces
+/// replaced by `-`.
+///
+/// # Example
+/// ```
+/// use mdx_rs::parser::markdown::slug::slugify;
+///
+/// assert_eq!(slugify("Getting Started!"), "getting-started");
+/// ```
+pub fn slugify(text: &str) -> String {
+    text.trim()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Generates slugs that are unique within a document by suffixing repeated slugs with `-1`, `-2`,
+/// and so on.
+#[derive(Debug, Default)]
+pub struct Slugger {
+    occurrences: HashMap<String, usize>,
+}
+
+impl Slugger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the unique slug for the text.
+    pub fn slug(&mut self, text: &str) -> String {
+        self.unique(&slugify(text))
+    }
+
+    /// Returns the slug, suffixed if it has already been used.
+    pub fn unique(&mut self, slug: &str) -> String {
+        let mut unique = slug.to_string();
+
+        while self.occurrences.contains_key(&unique) {
+            let count = self.occurrences.entry(slug.to_string()).or_default();
+            *count += 1;
+            unique = format!("{}-{}", slug, count);
+        }
+
+        self.occurrences.insert(unique.clone(), 0);
+        unique
+    }
+
+    /// Marks an explicit id as used so generated slugs don't collide with it.
+    pub fn reserve(&mut self, id: &str) {
+        self.occurrences.entry(id.to_string()).or_default();
+    }
+}
+
+#[cfg(test)]
+mod test_slug {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Installation"), "installation");
+        assert_eq!(slugify("What's new in v2.0?"), "whats-new-in-v20");
+        assert_eq!(slugify("snake_case and-dashes"), "snake_case-and-dashes");
+        assert_eq!(slugify("Übersicht  Größe"), "übersicht--größe");
+        assert_eq!(slugify("`code` & more"), "code--more");
+    }
+
+    #[test]
+    fn test_unique_slugs() {
+        let mut slugger = Slugger::new();
+
+        assert_eq!(slugger.slug("Usage"), "usage");
+        assert_eq!(slugger.slug("Usage"), "usage-1");
+        assert_eq!(slugger.slug("Usage"), "usage-2");
+        assert_eq!(slugger.slug("Usage 1"), "usage-1-1");
+
+        slugger.reserve("options");
+        assert_eq!(slugger.slug("Options"), "options-1");
+    }
+}
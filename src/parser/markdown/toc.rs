@@ -0,0 +1,284 @@
+use super::{Block, ListItem, Options, UnorderedList};
+use std::borrow::Cow;
+use std::ops::RangeInclusive;
+
+/// A heading in a table of contents along with the headings nested under it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub level: u8,
+    /// The text of the heading with inline markdown flattened to plain text.
+    pub text: String,
+    pub id: String,
+    pub children: Vec<Entry>,
+}
+
+/// The headings of a document nested by their level.
+///
+/// # Example
+/// ```
+/// use mdx_rs::parser::markdown::{toc::TableOfContents, Block};
+///
+/// let (_, blocks) = Block::parse("# Guide\n\n## Installation\n").unwrap();
+/// let toc = TableOfContents::new(&blocks);
+///
+/// assert_eq!(toc.entries[0].children[0].id, "installation");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableOfContents {
+    pub entries: Vec<Entry>,
+}
+
+impl TableOfContents {
+    /// Builds the table of contents from every heading.
+    pub fn new(blocks: &[Block]) -> Self {
+        Self::with_depth(blocks, 1..=6)
+    }
+
+    /// Builds the table of contents from the headings with a level within `depth`, including
+    /// those within containers such as admonitions.
+    pub fn with_depth(blocks: &[Block], depth: RangeInclusive<u8>) -> Self {
+        Self::with_options(blocks, depth, &Options::default())
+    }
+
+    /// Builds the table of contents like [`TableOfContents::with_depth`], flattening the
+    /// extension spans enabled in `options` out of the heading text.
+    pub fn with_options(blocks: &[Block], depth: RangeInclusive<u8>, options: &Options) -> Self {
+        let mut toc = Self::default();
+        let mut headings = Vec::new();
+        Block::headings(blocks, &mut headings);
+
+        for heading in headings {
+            if depth.contains(&heading.level) {
+                let entry = Entry {
+                    level: heading.level,
+                    text: heading.text_value_with(options),
+                    id: heading.id.to_string(),
+                    children: Vec::new(),
+                };
+
+                insert(&mut toc.entries, entry);
+            }
+        }
+
+        toc
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Renders the table of contents as a nested list of links to the headings.
+    pub fn to_block(&self) -> Block<'static> {
+        Block::UnorderedList(list(&self.entries))
+    }
+}
+
+/// Nests the entry under the last entry with a lower level, headings that skip a level are nested
+/// directly under the closest parent.
+fn insert(entries: &mut Vec<Entry>, entry: Entry) {
+    match entries.last_mut() {
+        Some(last) if last.level < entry.level => insert(&mut last.children, entry),
+        _ => entries.push(entry),
+    }
+}
+
+fn list(entries: &[Entry]) -> UnorderedList<'static> {
+    let items = entries
+        .iter()
+        .map(|entry| ListItem {
+            text: Cow::Owned(format!(
+                "[{}](#{})",
+                escape(&entry.text, "\\[]()`*_<>&!~^=${}"),
+                escape(&entry.id, "\\()<>")
+            )),
+            checked: None,
+            children: match entry.children.is_empty() {
+                true => Vec::new(),
+                false => vec![Block::UnorderedList(list(&entry.children))],
+            },
+        })
+        .collect();
+
+    UnorderedList {
+        items,
+        bullet: '-',
+        loose: false,
+    }
+}
+
+/// Escapes the characters that would otherwise be read as markdown with backslashes.
+fn escape(text: &str, special: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if special.contains(c) {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod test_toc {
+    use super::*;
+    use crate::parser::markdown::{Link, Options};
+    use crate::parser::Parse;
+    use indoc::indoc;
+
+    const INPUT: &str = indoc! {"
+        # Guide
+
+        ## Installation
+
+        ### From [crates.io](https://crates.io)
+
+        #### Features
+
+        ## Usage
+
+        Some text
+
+        #### Skipped a level
+
+        # Appendix
+    "};
+
+    fn titles(entries: &[Entry]) -> Vec<(String, Vec<String>)> {
+        entries
+            .iter()
+            .map(|entry| {
+                let children = entry.children.iter().map(|c| c.text.clone()).collect();
+                (entry.text.clone(), children)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_nesting() {
+        let (_, blocks) = Block::parse(INPUT).unwrap();
+        let toc = TableOfContents::new(&blocks);
+
+        assert_eq!(
+            titles(&toc.entries),
+            vec![
+                (
+                    "Guide".to_string(),
+                    vec!["Installation".to_string(), "Usage".to_string()]
+                ),
+                ("Appendix".to_string(), vec![]),
+            ]
+        );
+
+        let installation = &toc.entries[0].children[0];
+        assert_eq!(installation.children[0].text, "From crates.io");
+        assert_eq!(installation.children[0].id, "from-cratesio");
+        assert_eq!(installation.children[0].children[0].level, 4);
+
+        let usage = &toc.entries[0].children[1];
+        assert_eq!(usage.children[0].text, "Skipped a level");
+    }
+
+    #[test]
+    fn test_depth() {
+        let (_, blocks) = Block::parse(INPUT).unwrap();
+        let toc = TableOfContents::with_depth(&blocks, 2..=3);
+
+        assert_eq!(
+            titles(&toc.entries),
+            vec![
+                (
+                    "Installation".to_string(),
+                    vec!["From crates.io".to_string()]
+                ),
+                ("Usage".to_string(), vec![]),
+            ]
+        );
+
+        assert!(TableOfContents::with_depth(&blocks, 5..=6).is_empty());
+    }
+
+    #[test]
+    fn test_options() {
+        let options = Options {
+            strikethrough: true,
+            highlight: true,
+            ..Options::default()
+        };
+
+        let (_, blocks) = Block::parse_with("# Old ~~name~~ ==new==\n", &options).unwrap();
+        let toc = TableOfContents::with_options(&blocks, 1..=6, &options);
+
+        assert_eq!(toc.entries[0].text, "Old name new");
+    }
+
+    #[test]
+    fn test_containers() {
+        let input = indoc! {"
+            # Guide
+
+            :::note
+            ## Usage
+            :::
+
+            [^1]: A footnote.
+
+                ## Details
+        "};
+
+        let options = Options {
+            admonitions: true,
+            ..Options::default()
+        };
+
+        let (_, blocks) = Block::parse_with(input, &options).unwrap();
+        let toc = TableOfContents::new(&blocks);
+
+        assert_eq!(
+            titles(&toc.entries),
+            vec![(
+                "Guide".to_string(),
+                vec!["Usage".to_string(), "Details".to_string()]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_to_block() {
+        let (_, blocks) = Block::parse("# One\n\n## Two\n\n# Three\n").unwrap();
+        let toc = TableOfContents::new(&blocks);
+
+        assert_eq!(
+            toc.to_block(),
+            Block::UnorderedList(UnorderedList {
+                items: vec![
+                    ListItem {
+                        text: Cow::Borrowed("[One](#one)"),
+                        checked: None,
+                        children: vec![Block::UnorderedList(UnorderedList {
+                            items: vec!["[Two](#two)".into()],
+                            bullet: '-',
+                            loose: false,
+                        })],
+                    },
+                    "[Three](#three)".into(),
+                ],
+                bullet: '-',
+                loose: false,
+            })
+        );
+
+        let (_, blocks) = Block::parse("# Use `[x]` & *y* {#a(b)}\n").unwrap();
+        let Block::UnorderedList(list) = TableOfContents::new(&blocks).to_block() else {
+            panic!("expected a list");
+        };
+
+        assert_eq!(&list[0], r"[Use \[x\] \& y](#a\(b\))");
+
+        let (_, link) = Link::parse(&list[0]).unwrap();
+        assert_eq!(link.text_value(), "Use [x] & y");
+        assert_eq!(link.url, r"#a\(b\)");
+    }
+}